@@ -0,0 +1,138 @@
+import circom_tester from "circom_tester";
+import * as path from "path";
+import { readFileSync, writeFileSync } from "fs";
+import apis from "../../apis/pkg";
+import compiler from "../../compiler/pkg";
+const option = {
+  include: path.join(__dirname, "../../../node_modules"),
+};
+const wasm_tester = circom_tester.wasm;
+
+jest.setTimeout(600000);
+describe("Asterisk Regex", () => {
+  let circuit1;
+  beforeAll(async () => {
+    writeFileSync(
+      path.join(__dirname, "./circuits/common/asterisk1_regex.circom"),
+      compiler.genFromDecomposed(
+        readFileSync(
+          path.join(__dirname, "./circuits/common/asterisk1.json"),
+          "utf8"
+        ),
+        "Asterisk1Regex"
+      )
+    );
+    circuit1 = await wasm_tester(
+      path.join(__dirname, "./circuits/test_asterisk1_regex.circom"),
+      option
+    );
+  });
+
+  it("asterisk1 valid case 1", async () => {
+    const inputStr = `xb`;
+    const paddedStr = apis.padString(inputStr, 8);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(1n).toEqual(witness[1]);
+    const revealedIdx = [[]];
+    for (let substr_idx = 0; substr_idx < 1; ++substr_idx) {
+      for (let idx = 0; idx < 8; ++idx) {
+        if (revealedIdx[substr_idx].includes(idx)) {
+          expect(BigInt(paddedStr[idx])).toEqual(
+            witness[2 + 8 * substr_idx + idx]
+          );
+        } else {
+          expect(0n).toEqual(witness[2 + 8 * substr_idx + idx]);
+        }
+      }
+    }
+  });
+
+  it("asterisk1 valid case 2", async () => {
+    const inputStr = `xab`;
+    const paddedStr = apis.padString(inputStr, 8);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(1n).toEqual(witness[1]);
+    const revealedIdx = [[1]];
+    for (let substr_idx = 0; substr_idx < 1; ++substr_idx) {
+      for (let idx = 0; idx < 8; ++idx) {
+        if (revealedIdx[substr_idx].includes(idx)) {
+          expect(BigInt(paddedStr[idx])).toEqual(
+            witness[2 + 8 * substr_idx + idx]
+          );
+        } else {
+          expect(0n).toEqual(witness[2 + 8 * substr_idx + idx]);
+        }
+      }
+    }
+  });
+
+  it("asterisk1 valid case 3", async () => {
+    const inputStr = `710xaab98`;
+    const paddedStr = apis.padString(inputStr, 16);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(1n).toEqual(witness[1]);
+    const revealedIdx = [[4, 5]];
+    for (let substr_idx = 0; substr_idx < 1; ++substr_idx) {
+      for (let idx = 0; idx < 16; ++idx) {
+        if (revealedIdx[substr_idx].includes(idx)) {
+          expect(BigInt(paddedStr[idx])).toEqual(
+            witness[2 + 16 * substr_idx + idx]
+          );
+        } else {
+          expect(0n).toEqual(witness[2 + 16 * substr_idx + idx]);
+        }
+      }
+    }
+  });
+
+  it("asterisk1 valid case 4", async () => {
+    const inputStr = `xaab`;
+    const paddedStr = apis.padString(inputStr, 8);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(1n).toEqual(witness[1]);
+  });
+
+  it("asterisk1 invalid case 1", async () => {
+    const inputStr = `xaa`;
+    const paddedStr = apis.padString(inputStr, 8);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(0n).toEqual(witness[1]);
+    for (let idx = 0; idx < 8; ++idx) {
+      expect(0n).toEqual(witness[2 + idx]);
+    }
+  });
+
+  it("asterisk1 invalid case 2", async () => {
+    const inputStr = `aab`;
+    const paddedStr = apis.padString(inputStr, 8);
+    const circuitInputs = {
+      msg: paddedStr,
+    };
+    const witness = await circuit1.calculateWitness(circuitInputs);
+    await circuit1.checkConstraints(witness);
+    expect(0n).toEqual(witness[1]);
+    for (let idx = 0; idx < 8; ++idx) {
+      expect(0n).toEqual(witness[2 + idx]);
+    }
+  });
+});
@@ -0,0 +1,5 @@
+pragma circom 2.1.5;
+
+include "./common/asterisk1_regex.circom";
+
+component main = Asterisk1Regex(8);
@@ -1,15 +1,10 @@
-use std::{
-    fmt::Display,
-    fs,
-    path::{Path, PathBuf},
-    process::Command,
-};
+use std::{fmt::Display, fs, path::Path, process::Command};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{constants, BenchExecType};
+use crate::{bytes::ByteString, constants, workspace::Workspace, BenchExecType};
 
 /// Error that may happen in the command execution.
 #[derive(thiserror::Error, Debug)]
@@ -38,6 +33,9 @@ pub struct BenchResult {
     /// Tells if this benchmark was performed using the gen_substr() function.
     #[serde(skip_deserializing)]
     pub with_gen_substr: bool,
+    /// Input size of the benchmarked circuit.
+    #[serde(skip_deserializing)]
+    pub input_size: usize,
     /// Time spent in the proving.
     #[serde(skip_deserializing)]
     pub proving_time: f64,
@@ -93,16 +91,17 @@ impl BenchReport {
 /// command `bb gates -b <target>`.
 pub fn benchmark_noir_code(
     input_size: usize,
-    benchmark_str: String,
+    benchmark_str: ByteString,
     bench_exec_type: &BenchExecType,
+    workspace: &Workspace,
 ) -> anyhow::Result<BenchResult> {
     // Count the gates and create the BenchResult. The gates can be counted always.
-    let mut bench_result = count_gates()?;
+    let mut bench_result = count_gates(workspace)?;
 
     match bench_exec_type {
         BenchExecType::WithTime => {
-            modify_prover_toml(input_size, benchmark_str)?;
-            let avg_proving_time = execute_proving_time_command()?;
+            modify_prover_toml(input_size, benchmark_str, workspace)?;
+            let avg_proving_time = execute_proving_time_command(workspace)?;
             bench_result.proving_time = avg_proving_time;
             bench_result.with_time = true;
         }
@@ -114,25 +113,34 @@ pub fn benchmark_noir_code(
     Ok(bench_result)
 }
 
-/// Modifies the Prover.toml file to have the right input size to measure the proving time.
-pub fn modify_prover_toml(input_size: usize, benchmark_str: String) -> anyhow::Result<()> {
+/// Checks that the benchmarking string has the length of the input of the circuit.
+pub fn check_benchmark_str(input_size: usize, benchmark_str: &ByteString) -> anyhow::Result<()> {
     if input_size != benchmark_str.len() {
         anyhow::bail!(Error::BadDbInput(input_size, benchmark_str.len()));
     }
+    Ok(())
+}
 
-    // Fill the input element with a random vector with the respective input size.
-    let contents_int = benchmark_str.chars().map(|c| c as u8).collect::<Vec<u8>>();
-    let contents = format!("input = {:?}", contents_int);
-    fs::write(constants::DEFAULT_PROVER_TOML_PATH, contents)?;
+/// Modifies the Prover.toml file to have the right input size to measure the proving time.
+pub fn modify_prover_toml(
+    input_size: usize,
+    benchmark_str: ByteString,
+    workspace: &Workspace,
+) -> anyhow::Result<()> {
+    check_benchmark_str(input_size, &benchmark_str)?;
+
+    // Fill the input element with the bytes of the benchmarking string.
+    let contents = format!("input = {:?}", benchmark_str.as_bytes());
+    fs::write(workspace.prover_toml_path(), contents)?;
     Ok(())
 }
 
 /// Executes the command to count the number of gates.
-pub fn count_gates() -> anyhow::Result<BenchResult> {
+pub fn count_gates(workspace: &Workspace) -> anyhow::Result<BenchResult> {
     let output = Command::new("bb")
         .args(["gates", "-b"])
         .arg(constants::DEFAULT_TARJET_JSON_FILE)
-        .current_dir(constants::DEFAULT_PROJECT_PATH)
+        .current_dir(workspace.project_path())
         .output()
         .context("the gate-count command was not executed correctly")?;
     if !output.status.success() {
@@ -153,11 +161,11 @@ pub fn count_gates() -> anyhow::Result<BenchResult> {
 
 /// Counts the number of seconds spent in the proving time as an average of
 /// 3 executions. This average is computed using the `hyperfine` command.
-pub fn execute_proving_time_command() -> anyhow::Result<f64> {
+pub fn execute_proving_time_command(workspace: &Workspace) -> anyhow::Result<f64> {
     // Generate the witness
     let output = Command::new("nargo")
         .args(["execute", constants::DEFAULT_WITNESS_NAME])
-        .current_dir(constants::DEFAULT_PROJECT_PATH)
+        .current_dir(workspace.project_path())
         .output()
         .context("error generating the witness while measuring the proving time")?;
     if !output.status.success() {
@@ -167,16 +175,13 @@ pub fn execute_proving_time_command() -> anyhow::Result<f64> {
         ));
     }
 
-    // Defines a path to the previous directory ".."
-    let mut prev_path = PathBuf::from("..");
-
-    // Creates the path ../<JSON_PATH>
-    prev_path.push(Path::new(constants::DEFAULT_PROVING_TIME_RESULT_FILE));
-    // It is safe to do unwrap because we are sure that the string is correct.
-    let path_json_time_str = prev_path.as_path().to_str().unwrap();
+    // The command is executed inside the project, so the path of the JSON must be absolute.
+    let path_json_time = std::path::absolute(workspace.proving_time_result_file())
+        .context("error building the path of the timing report")?;
 
     let output = Command::new("hyperfine")
-        .args(["--export-json", path_json_time_str])
+        .arg("--export-json")
+        .arg(&path_json_time)
         .args(["--runs", "5"])
         .arg("--show-output")
         .args(["--time-unit", "millisecond"])
@@ -186,7 +191,7 @@ pub fn execute_proving_time_command() -> anyhow::Result<f64> {
             constants::DEFAULT_WITNESS_PATH,
             constants::DEFAULT_PROOF_PATH
         ))
-        .current_dir(constants::DEFAULT_PROJECT_PATH)
+        .current_dir(workspace.project_path())
         .output()
         .context("error executing the proving time command")?;
     if !output.status.success() {
@@ -197,7 +202,7 @@ pub fn execute_proving_time_command() -> anyhow::Result<f64> {
     }
 
     // Extract the results from the JSON file
-    let result_json_str = fs::read_to_string(constants::DEFAULT_PROVING_TIME_RESULT_FILE)?;
+    let result_json_str = fs::read_to_string(path_json_time)?;
     let value_result: Value = serde_json::from_str(&result_json_str)?;
     let avg_time = value_result["results"][0]["mean"]
         .as_f64()
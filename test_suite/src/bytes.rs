@@ -0,0 +1,262 @@
+use std::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+};
+
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// Errors that can arise when reading the bytes of a sample.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The hexadecimal form of the bytes has an odd number of digits or a character that is not
+    /// a hexadecimal digit.
+    #[error("invalid hexadecimal bytes: {0:?}")]
+    InvalidHex(String),
+    /// The escaped form of the bytes has an unknown or incomplete escape sequence.
+    #[error("invalid escape sequence in {0:?}")]
+    InvalidEscape(String),
+}
+
+/// Input of a sample or a substring as raw bytes, which don't need to be valid UTF-8. In the
+/// database, the bytes are written as one of the forms of [`ByteStringForm`].
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(try_from = "ByteStringForm", into = "ByteStringForm")]
+pub struct ByteString(Vec<u8>);
+
+/// Forms of the bytes of a sample in the database.
+#[derive(Deserialize, Serialize, JsonSchema)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum ByteStringForm {
+    /// A string, whose bytes are its UTF-8 encoding.
+    Text(String),
+    /// Bytes written as hexadecimal digits, e.g. `{"hex": "00ff"}`. Whitespace between the
+    /// bytes is ignored.
+    Hex {
+        /// The hexadecimal digits of the bytes.
+        hex: String,
+    },
+    /// Bytes written as a string with the escape sequences `\xNN`, `\n`, `\r`, `\t`, `\0` and
+    /// `\\`, e.g. `{"escaped": "\\x00\\xff"}`. The other characters stand for their UTF-8
+    /// encoding.
+    Escaped {
+        /// The string with the escape sequences.
+        escaped: String,
+    },
+}
+
+impl ByteString {
+    /// Returns the bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Whether there are no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Splits the bytes into characters: the UTF-8 encoded characters and the single bytes that
+    /// are not part of a valid UTF-8 character.
+    pub fn chars(&self) -> Vec<&[u8]> {
+        let mut chars = Vec::new();
+        for chunk in self.0.utf8_chunks() {
+            let mut valid = chunk.valid().as_bytes();
+            for c in chunk.valid().chars() {
+                let (char_bytes, rest) = valid.split_at(c.len_utf8());
+                chars.push(char_bytes);
+                valid = rest;
+            }
+            chars.extend(chunk.invalid().chunks(1));
+        }
+        chars
+    }
+}
+
+impl Deref for ByteString {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteString {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<String> for ByteString {
+    fn from(string: String) -> Self {
+        Self(string.into_bytes())
+    }
+}
+
+impl From<&str> for ByteString {
+    fn from(string: &str) -> Self {
+        Self(string.as_bytes().to_vec())
+    }
+}
+
+impl TryFrom<ByteStringForm> for ByteString {
+    type Error = Error;
+
+    fn try_from(form: ByteStringForm) -> Result<Self, Self::Error> {
+        match form {
+            ByteStringForm::Text(text) => Ok(text.into()),
+            ByteStringForm::Hex { hex } => {
+                let digits: Vec<u8> = hex.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+                if !digits.len().is_multiple_of(2) {
+                    return Err(Error::InvalidHex(hex));
+                }
+                digits
+                    .chunks(2)
+                    .map(|pair| {
+                        std::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .map(Self)
+                    .ok_or(Error::InvalidHex(hex))
+            }
+            ByteStringForm::Escaped { escaped } => unescape(&escaped)
+                .map(Self)
+                .ok_or(Error::InvalidEscape(escaped)),
+        }
+    }
+}
+
+/// Valid UTF-8 bytes are written as a string and the other ones in hexadecimal.
+impl From<ByteString> for ByteStringForm {
+    fn from(bytes: ByteString) -> Self {
+        match String::from_utf8(bytes.0) {
+            Ok(text) => ByteStringForm::Text(text),
+            Err(err) => ByteStringForm::Hex {
+                hex: err
+                    .as_bytes()
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl JsonSchema for ByteString {
+    fn schema_name() -> String {
+        "ByteString".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        ByteStringForm::json_schema(gen)
+    }
+}
+
+/// Evaluates the escape sequences of the escaped form of some bytes. Returns `None` for an
+/// unknown or incomplete escape sequence.
+fn unescape(escaped: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        let byte = match chars.next()? {
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+                if code.len() != 2 {
+                    return None;
+                }
+                u8::from_str_radix(&code, 16).ok()?
+            }
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            '0' => b'\0',
+            '\\' => b'\\',
+            _ => return None,
+        };
+        bytes.push(byte);
+    }
+    Some(bytes)
+}
+
+/// Writes the bytes as a quoted string, with the escapes of Rust strings for the valid UTF-8
+/// characters and `\xNN` for the other bytes.
+impl Debug for ByteString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self)
+    }
+}
+
+/// Writes the bytes with the escapes of Rust strings for the valid UTF-8 characters and `\xNN`
+/// for the other bytes.
+impl Display for ByteString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.0.utf8_chunks() {
+            // The valid characters are escaped as in the debug format of strings
+            let valid = format!("{:?}", chunk.valid());
+            write!(f, "{}", &valid[1..valid.len() - 1])?;
+            for byte in chunk.invalid() {
+                write!(f, "\\x{:02x}", byte)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<ByteString, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn reads_the_forms_of_the_bytes() {
+        assert_eq!(parse(r#""aé""#).unwrap().as_bytes(), "aé".as_bytes());
+        assert_eq!(
+            parse(r#"{"hex": "00 ff"}"#).unwrap().as_bytes(),
+            &[0x00, 0xff]
+        );
+        assert_eq!(
+            parse(r#"{"escaped": "a\\xff\\r\\n\\\\"}"#)
+                .unwrap()
+                .as_bytes(),
+            b"a\xff\r\n\\"
+        );
+        assert!(parse(r#"{"hex": "0ff"}"#).is_err());
+        assert!(parse(r#"{"hex": "zz"}"#).is_err());
+        assert!(parse(r#"{"escaped": "\\x0"}"#).is_err());
+        assert!(parse(r#"{"escaped": "\\q"}"#).is_err());
+    }
+
+    #[test]
+    fn writes_the_invalid_utf8_bytes_in_hexadecimal() {
+        let text = ByteString::from("a\n");
+        assert_eq!(serde_json::to_string(&text).unwrap(), r#""a\n""#);
+        let bytes = ByteString::from(vec![b'a', 0xff]);
+        let json = serde_json::to_string(&bytes).unwrap();
+        assert_eq!(json, r#"{"hex":"61ff"}"#);
+        assert_eq!(parse(&json).unwrap(), bytes);
+    }
+
+    #[test]
+    fn escapes_the_invalid_utf8_bytes() {
+        let bytes = ByteString::from(vec![b'a', b'\n', 0xff, b'"']);
+        assert_eq!(bytes.to_string(), r#"a\n\xff\""#);
+        assert_eq!(format!("{:?}", bytes), r#""a\n\xff\"""#);
+    }
+
+    #[test]
+    fn splits_the_bytes_into_characters() {
+        let bytes = ByteString::from(vec![b'a', 0xc3, 0xa9, 0xff, 0xc3]);
+        let chars: Vec<&[u8]> = vec![b"a", &[0xc3, 0xa9], &[0xff], &[0xc3]];
+        assert_eq!(bytes.chars(), chars);
+    }
+}
@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use regex::Regex;
+
+use crate::{
+    bytes::ByteString,
+    constants,
+    db::{DbEntry, InputWithSubstrs, RegexFragment, RegexInput, SamplesPass},
+};
+
+/// Definition of the regex of a circuit in the zk-regex repository, e.g. `circuits/asterisk1.json`.
+#[derive(serde::Deserialize)]
+struct DecomposedDefinition {
+    parts: Vec<RegexFragment>,
+}
+
+/// Test case of a circom test file.
+struct CircomTestCase {
+    /// Name given to `it`, e.g. `asterisk1 valid case 1`.
+    name: String,
+    /// Variable of the circuit that computes the witness.
+    circuit: String,
+    /// Input bytes of the test.
+    input: ByteString,
+    /// Whether the circuit should accept the input.
+    should_pass: bool,
+    /// Positions of the input revealed by each public part of the regex, as checked by the
+    /// test in the `revealedIdx` array, e.g. `[[1, 2]]`.
+    revealed_indexes: Option<Vec<Vec<usize>>>,
+}
+
+/// Reads a circom test file of zk-regex (e.g. `asterisk.test.js`) together with the regex
+/// definitions of its circuits, and returns an entry for each circuit with the inputs of its
+/// test cases:
+/// - the circuits are generated with `compiler.genFromDecomposed` from a JSON definition and
+///   loaded with `wasm_tester`, usually through a `test_*` wrapper of the generated file. The
+///   name of the JSON file is the `circuit_name`.
+/// - each `it` call is a test case, whose name is the `circom_testname` of the sample. The input
+///   is the first string (or array of bytes) declared in the test, and the test expects a match
+///   if it checks `expect(1n).toEqual(witness[1])`, or no match if it checks `0n` or expects the
+///   witness calculation to throw.
+/// - the expected substrings of the passing inputs are the bytes of the input at the positions
+///   the test expects each public part to reveal (`revealedIdx`).
+///
+/// Test cases that can't be read, including the passing ones of a circuit with public parts
+/// whose revealed positions are not given, are skipped with a warning.
+pub fn import_test_file(path: &Path) -> anyhow::Result<Vec<DbEntry>> {
+    let contents = fs::read_to_string(path)
+        .context(format!("error reading the circom tests in {:?}", path))?;
+    let circuits = circuit_definitions(&contents, path)?;
+    let test_file = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string());
+
+    let mut entries: Vec<DbEntry> = Vec::new();
+    let mut entry_positions: HashMap<PathBuf, usize> = HashMap::new();
+    for test_case in read_test_cases(&contents, path)? {
+        let Some(definition_path) = circuits.get(&test_case.circuit) else {
+            log::warn!(
+                "skipping the circom test {:?}: the circuit {} is unknown or not generated from a decomposed regex",
+                test_case.name,
+                test_case.circuit
+            );
+            continue;
+        };
+        let position = match entry_positions.get(definition_path) {
+            Some(&position) => position,
+            None => {
+                let mut entry = new_entry(definition_path)?;
+                entry.test_name.clone_from(&test_file);
+                entries.push(entry);
+                entry_positions.insert(definition_path.clone(), entries.len() - 1);
+                entries.len() - 1
+            }
+        };
+        add_test_case(&mut entries[position], test_case);
+    }
+    Ok(entries)
+}
+
+/// Finds the circuits of a circom test file that are generated with `compiler.genFromDecomposed`
+/// and loaded with `wasm_tester`, and returns the path of the JSON definition of each circuit
+/// variable. The other circuits are left out.
+///
+/// The loaded file is usually a wrapper of the generated one in another folder, e.g.
+/// `circuits/test_asterisk1_regex.circom` for `circuits/common/asterisk1_regex.circom`
+/// generated from `circuits/common/asterisk1.json`, so the files are matched by the name of
+/// the regex (see [`regex_name`]) of the generated file or of its JSON definition.
+fn circuit_definitions(contents: &str, path: &Path) -> anyhow::Result<HashMap<String, PathBuf>> {
+    let test_dir = path.parent().unwrap_or(Path::new("."));
+
+    // Regex name -> JSON definition of the regex
+    let generation_regex = Regex::new(
+        r#"writeFileSync\(\s*path\.join\(\s*__dirname\s*,\s*["'`]([^"'`]+)["'`]\s*\)\s*,\s*compiler\.genFromDecomposed\(\s*readFileSync\(\s*path\.join\(\s*__dirname\s*,\s*["'`]([^"'`]+)["'`]"#,
+    )?;
+    let mut definitions: HashMap<String, PathBuf> = HashMap::new();
+    for captures in generation_regex.captures_iter(contents) {
+        let definition = test_dir.join(&captures[2]);
+        for file in [&captures[1], &captures[2]] {
+            definitions
+                .entry(regex_name(Path::new(file)))
+                .or_insert_with(|| definition.clone());
+        }
+    }
+
+    // Circuit variable -> loaded circom file
+    let tester_regex = Regex::new(
+        r#"(\w+)\s*=\s*await\s+wasm_tester\(\s*path\.join\(\s*__dirname\s*,\s*["'`]([^"'`]+)["'`]"#,
+    )?;
+    Ok(tester_regex
+        .captures_iter(contents)
+        .filter_map(|captures| {
+            let definition = definitions.get(&regex_name(Path::new(&captures[2])))?;
+            Some((captures[1].to_string(), definition.clone()))
+        })
+        .collect())
+}
+
+/// Returns the name of the regex of a circom file or of a JSON definition, which is the name of
+/// the file without the `test_` prefix of the test wrappers and the `_regex` suffix of the
+/// generated files, e.g. `asterisk1` for `test_asterisk1_regex.circom`, `asterisk1_regex.circom`
+/// and `asterisk1.json`.
+fn regex_name(file: &Path) -> String {
+    let stem = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = stem.strip_prefix("test_").unwrap_or(&stem);
+    name.strip_suffix("_regex").unwrap_or(name).to_string()
+}
+
+/// Returns the `circuit_name` of the entry of the circuit defined in the given JSON file.
+fn circuit_name(definition_path: &Path) -> Option<String> {
+    definition_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+}
+
+/// Returns the name of each test case (`it` call) of a circom test file, together with the
+/// `circuit_name` of the circuit it tests when the circuit is generated from a decomposed regex.
+pub fn test_case_circuits(
+    contents: &str,
+    path: &Path,
+) -> anyhow::Result<Vec<(String, Option<String>)>> {
+    let circuits = circuit_definitions(contents, path)?;
+    let circuit_regex = Regex::new(r"(\w+)\.calculateWitness")?;
+    Ok(test_calls(contents)?
+        .into_iter()
+        .map(|(name, body)| {
+            let circuit = circuit_regex
+                .captures(body)
+                .and_then(|circuit| circuits.get(&circuit[1]))
+                .and_then(|definition_path| circuit_name(definition_path));
+            (name, circuit)
+        })
+        .collect())
+}
+
+/// Creates an entry without samples for the circuit defined in the given JSON file.
+fn new_entry(definition_path: &Path) -> anyhow::Result<DbEntry> {
+    let definition: DecomposedDefinition =
+        serde_json::from_str(&fs::read_to_string(definition_path).context(format!(
+            "error reading the regex definition {:?}",
+            definition_path
+        ))?)
+        .context(format!(
+            "error parsing the regex definition {:?}",
+            definition_path
+        ))?;
+    let entry = serde_json::json!({
+        "circuit_name": circuit_name(definition_path),
+        "regex": RegexInput::Decomposed(definition.parts),
+        "gen_substrs": true,
+        "input_size": constants::DEFAULT_IMPORT_INPUT_SIZE,
+        "samples_pass": [],
+        "samples_fail": [],
+    });
+    serde_json::from_value(entry).context("error creating the imported entry")
+}
+
+/// Adds the input of a test case to the passing or failing samples of the entry. A passing test
+/// case whose expected substrings can't be read is skipped with a warning.
+fn add_test_case(entry: &mut DbEntry, test_case: CircomTestCase) {
+    if !test_case.should_pass {
+        entry.samples_fail.push(test_case.input);
+        entry.circom_testnames_fails.push(test_case.name);
+        return;
+    }
+    let RegexInput::Decomposed(parts) = &entry.regex else {
+        return;
+    };
+    let Some(expected_substrings) = revealed_substrings(parts, &test_case) else {
+        log::warn!(
+            "skipping the circom test {:?}: the substrings it expects the public parts to reveal were not found",
+            test_case.name
+        );
+        return;
+    };
+    if let SamplesPass::WithSubstrs(samples) = &mut entry.samples_pass {
+        samples.push(InputWithSubstrs {
+            input: test_case.input,
+            expected_substrings,
+            circom_testname: Some(test_case.name),
+        });
+    }
+}
+
+/// Returns the substrings revealed by the public parts of the regex according to the test case:
+/// each run of consecutive positions revealed by a public part is a substring. Returns `None`
+/// if the regex has public parts, but the test doesn't give a list of positions for each of
+/// them within the input.
+fn revealed_substrings(
+    parts: &[RegexFragment],
+    test_case: &CircomTestCase,
+) -> Option<Vec<ByteString>> {
+    let public_parts = parts.iter().filter(|part| part.is_public).count();
+    if public_parts == 0 {
+        return Some(Vec::new());
+    }
+    let revealed_indexes = test_case.revealed_indexes.as_ref()?;
+    if revealed_indexes.len() != public_parts {
+        return None;
+    }
+    let mut substrings = Vec::new();
+    for indexes in revealed_indexes {
+        let mut substring: Vec<u8> = Vec::new();
+        for (position, &index) in indexes.iter().enumerate() {
+            if position > 0 && indexes[position - 1] + 1 != index {
+                substrings.push(ByteString::from(std::mem::take(&mut substring)));
+            }
+            substring.push(*test_case.input.get(index)?);
+        }
+        if !substring.is_empty() {
+            substrings.push(ByteString::from(substring));
+        }
+    }
+    Some(substrings)
+}
+
+/// Returns the name and the body of each test case (`it` or `test` call) of a circom test file.
+/// The body is the text up to the next test case.
+fn test_calls(contents: &str) -> anyhow::Result<Vec<(String, &str)>> {
+    let test_call_regex = Regex::new(r#"\b(?:it|test)\s*\(\s*(?:"([^"]*)"|'([^']*)'|`([^`]*)`)"#)?;
+    let calls: Vec<_> = test_call_regex.captures_iter(contents).collect();
+    Ok(calls
+        .iter()
+        .enumerate()
+        .map(|(position, captures)| {
+            let name = captures
+                .iter()
+                .skip(1)
+                .flatten()
+                .next()
+                .map_or("", |name| name.as_str())
+                .to_string();
+            let start = captures.get(0).map_or(0, |m| m.end());
+            let end = calls
+                .get(position + 1)
+                .and_then(|next| next.get(0))
+                .map_or(contents.len(), |m| m.start());
+            (name, &contents[start..end])
+        })
+        .collect())
+}
+
+/// Reads the test cases (`it` calls) of a circom test file.
+fn read_test_cases(contents: &str, path: &Path) -> anyhow::Result<Vec<CircomTestCase>> {
+    let input_regex = Regex::new(
+        r#"const\s+\w+\s*=\s*(?:"((?:[^"\\]|\\.)*)"|'((?:[^'\\]|\\.)*)'|`((?:[^`\\]|\\.)*)`|\[([\d\s,]*)\])"#,
+    )?;
+    let circuit_regex = Regex::new(r"(\w+)\.calculateWitness")?;
+    let expectation_regex = Regex::new(r"expect\(\s*([01])n\s*\)\.toEqual\(\s*witness\[1\]\s*\)")?;
+    let revealed_regex = Regex::new(r"revealedIdx\s*=\s*(\[[\[\]\d\s,]*\])")?;
+
+    let mut test_cases = Vec::new();
+    for (name, body) in test_calls(contents)? {
+        let input = input_regex.captures(body).and_then(|input| {
+            if let Some(bytes) = input.get(4) {
+                let bytes = bytes
+                    .as_str()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|byte| !byte.is_empty())
+                    .map(str::parse::<u8>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()?;
+                Some(ByteString::from(bytes))
+            } else {
+                let literal = input.iter().skip(1).flatten().next()?.as_str();
+                // Template literals with placeholders can't be evaluated
+                (!literal.contains("${"))
+                    .then(|| unescape_js(literal))
+                    .flatten()
+                    .map(ByteString::from)
+            }
+        });
+        let circuit = circuit_regex
+            .captures(body)
+            .map(|circuit| circuit[1].to_string());
+        let should_pass = match expectation_regex.captures(body) {
+            Some(expectation) => Some(&expectation[1] == "1"),
+            None if body.contains(".rejects") || body.contains("toThrow") => Some(false),
+            None => None,
+        };
+        // The positions are only expected if the test checks the revealed outputs
+        let revealed_indexes = revealed_regex
+            .captures(body)
+            .filter(|_| body.contains("revealedIdx[") && body.contains("toEqual("))
+            .and_then(|revealed| serde_json::from_str(&revealed[1]).ok());
+
+        match (input, circuit, should_pass) {
+            (Some(input), Some(circuit), Some(should_pass)) => test_cases.push(CircomTestCase {
+                name,
+                circuit,
+                input,
+                should_pass,
+                revealed_indexes,
+            }),
+            _ => log::warn!(
+                "skipping the circom test {:?} of {:?}: its input, circuit or expected outcome was not found",
+                name,
+                path
+            ),
+        }
+    }
+    Ok(test_cases)
+}
+
+/// Evaluates the escape sequences of a JavaScript string literal and returns its bytes. A
+/// `\xNN` escape is the byte `NN`, so that the tests of binary inputs keep their bytes, and the
+/// other characters are encoded in UTF-8. Returns `None` for invalid escape sequences.
+fn unescape_js(literal: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut chars = literal.chars();
+    let push_char = |result: &mut Vec<u8>, c: char| {
+        result.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes())
+    };
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            push_char(&mut result, c);
+            continue;
+        }
+        let escaped = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'x' => {
+                let code: String = chars.by_ref().take(2).collect();
+                result.push(u8::from_str_radix(&code, 16).ok()?);
+                continue;
+            }
+            'u' => {
+                let code: String = if chars.as_str().starts_with('{') {
+                    let code: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                    code
+                } else {
+                    chars.by_ref().take(4).collect()
+                };
+                char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+            }
+            other => other,
+        };
+        push_char(&mut result, escaped);
+    }
+    Some(result)
+}
+
+/// Number of entries and samples added to the database by a merge.
+#[derive(Default)]
+pub struct MergeStats {
+    /// Number of new entries.
+    pub entries: usize,
+    /// Number of samples added to existing entries.
+    pub samples: usize,
+}
+
+/// Merges imported entries into the database. The samples of a circuit that already has an
+/// entry with the same `circuit_name` are added to that entry, unless their `circom_testname`
+/// is already in it, and the entry gets the `test_name` of the imported one if it has none.
+/// The other entries are appended.
+pub fn merge_entries(database: &mut Vec<DbEntry>, imported: Vec<DbEntry>) -> MergeStats {
+    let mut stats = MergeStats::default();
+    for entry in imported {
+        let Some(existing) = database.iter_mut().find(|existing| {
+            existing.circuit_name.is_some() && existing.circuit_name == entry.circuit_name
+        }) else {
+            database.push(entry);
+            stats.entries += 1;
+            continue;
+        };
+
+        if existing.test_name.is_none() {
+            existing.test_name = entry.test_name;
+        }
+        let existing_testnames: Vec<String> =
+            existing.circom_testnames().map(str::to_string).collect();
+        if let (SamplesPass::WithSubstrs(existing_samples), SamplesPass::WithSubstrs(samples)) =
+            (&mut existing.samples_pass, entry.samples_pass)
+        {
+            for sample in samples {
+                if !sample
+                    .circom_testname
+                    .as_ref()
+                    .is_some_and(|name| existing_testnames.contains(name))
+                {
+                    existing_samples.push(sample);
+                    stats.samples += 1;
+                }
+            }
+        }
+        for (sample, name) in entry
+            .samples_fail
+            .into_iter()
+            .zip(entry.circom_testnames_fails)
+        {
+            if !existing_testnames.contains(&name) {
+                existing.samples_fail.push(sample);
+                existing.circom_testnames_fails.push(name);
+                stats.samples += 1;
+            }
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_the_test_cases_of_a_circom_test_file() {
+        let entries = import_test_file(Path::new("fixtures/circom/asterisk.test.js")).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.circuit_name.as_deref(), Some("asterisk1"));
+        assert_eq!(entry.test_name.as_deref(), Some("asterisk.test.js"));
+
+        let SamplesPass::WithSubstrs(samples) = &entry.samples_pass else {
+            panic!("the imported samples have no substrings");
+        };
+        let samples: Vec<(&str, Vec<ByteString>)> = samples
+            .iter()
+            .map(|sample| {
+                (
+                    sample.circom_testname.as_deref().unwrap(),
+                    sample.expected_substrings.clone(),
+                )
+            })
+            .collect();
+        // The valid case 4 doesn't check the revealed substrings
+        assert_eq!(
+            samples,
+            [
+                ("asterisk1 valid case 1", vec![]),
+                ("asterisk1 valid case 2", vec!["a".into()]),
+                ("asterisk1 valid case 3", vec!["aa".into()]),
+            ]
+        );
+        assert_eq!(entry.samples_fail, [ByteString::from("xaa"), "aab".into()]);
+        assert_eq!(
+            entry.circom_testnames_fails,
+            ["asterisk1 invalid case 1", "asterisk1 invalid case 2"]
+        );
+    }
+
+    #[test]
+    fn matches_the_circuits_by_regex_name() {
+        assert_eq!(
+            regex_name(Path::new("./circuits/test_asterisk1_regex.circom")),
+            "asterisk1"
+        );
+        assert_eq!(
+            regex_name(Path::new("./circuits/common/asterisk1_regex.circom")),
+            "asterisk1"
+        );
+        assert_eq!(
+            regex_name(Path::new("./circuits/common/asterisk1.json")),
+            "asterisk1"
+        );
+    }
+
+    #[test]
+    fn keeps_the_hex_escapes_as_raw_bytes() {
+        assert_eq!(unescape_js(r"a\xff\r\n").unwrap(), b"a\xff\r\n");
+        assert_eq!(unescape_js(r"éé").unwrap(), "éé".as_bytes());
+        assert_eq!(unescape_js(r"\u{1F600}").unwrap(), "😀".as_bytes());
+        assert!(unescape_js(r"\xg0").is_none());
+    }
+}
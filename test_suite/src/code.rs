@@ -8,10 +8,14 @@ use std::{
 use std::fmt::Write;
 
 use anyhow::Context;
+use serde::Serialize;
 
 use crate::{
-    constants::{self, DEFAULT_DECOMPOSED_JSON_FILE, DEFAULT_SUBSTRS_JSON_PATH},
+    bytes::ByteString,
     db::{ComponentsWrapper, DbEntry, InputWithSubstrs, RawRegex, RegexInput},
+    padding::zero_padded,
+    transitions,
+    workspace::Workspace,
 };
 
 /// Errors that can arise when generating the Noir code
@@ -23,125 +27,150 @@ pub enum Error {
     CodeGenerationFailed(String),
 }
 
+/// A sample that is turned into a Noir test function.
+#[derive(Debug, Clone)]
+pub enum NoirTest {
+    /// Test that only checks whether the input matches the regex.
+    Standard {
+        /// The input bytes.
+        input: ByteString,
+        /// Whether the regex match is expected to fail for this input.
+        should_fail: bool,
+    },
+    /// Test that checks the match and the extracted substrings. These tests are always
+    /// expected to pass.
+    Substring(InputWithSubstrs),
+}
+
+impl NoirTest {
+    /// Returns the input bytes of the test.
+    pub fn input(&self) -> &ByteString {
+        match self {
+            NoirTest::Standard { input, .. } => input,
+            NoirTest::Substring(sample) => &sample.input,
+        }
+    }
+}
+
 /// Represents the information to construct a noir code.
 pub struct Code {
     /// Code generated using the zk-email tool.
     noir_code: String,
     /// Input size of provided to the main function in the Noir project.
     input_size: usize,
+    /// Whether the inputs of the tests are padded with zeros up to the input size.
+    pad_test_inputs: bool,
 }
 
 impl Code {
     /// Creates a new code from the inputs of the database.
-    pub fn new(regex_input: &DbEntry) -> anyhow::Result<Self> {
-        let noir_code = generate_noir_code(
-            &regex_input.regex,
-            regex_input.gen_substrs,
-            Path::new(constants::DEFAULT_GENERATION_PATH),
-        )
-        .context("error generating the noir code")?;
+    pub fn new(regex_input: &DbEntry, workspace: &Workspace) -> anyhow::Result<Self> {
+        let noir_code = generate_noir_code(&regex_input.regex, regex_input.gen_substrs, workspace)
+            .context("error generating the noir code")?;
         Ok(Self {
             noir_code,
             input_size: regex_input.input_size,
+            pad_test_inputs: regex_input.pad_test_inputs,
         })
     }
 
+    /// Returns the code generated using the zk-regex tool.
+    pub fn noir_code(&self) -> &str {
+        &self.noir_code
+    }
+
     /// Writes the current source code into a file in a given path.
     pub fn write_to_path(&self, path: &Path) -> anyhow::Result<()> {
-        fs::write(path, self.print_code(None, None, false))
+        fs::write(path, self.print_code(&[]))
             .context(format!("error writing the code to the path {:?}", path))?;
         Ok(())
     }
-    pub fn write_test_to_path(
-        &self,
-        standard_test: Option<&String>,
-        gen_substr_test: Option<&InputWithSubstrs>,
-        should_fail: bool,
-        path: &Path,
-    ) -> anyhow::Result<()> {
-        fs::write(
-            path,
-            self.print_code(standard_test, gen_substr_test, should_fail),
-        )
-        .context(format!("error writing the code to the path {:?}", path))?;
+
+    /// Writes the current source code together with a batch of tests into a file in a given path.
+    pub fn write_test_to_path(&self, tests: &[NoirTest], path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.print_code(tests))
+            .context(format!("error writing the code to the path {:?}", path))?;
         Ok(())
     }
 
-    pub fn print_code(
-        &self,
-        standard_test: Option<&String>,
-        gen_substr_test: Option<&InputWithSubstrs>,
-        // gen_substrs tests always pass, but standard tests might be expected to fail
-        should_fail: bool,
-    ) -> String {
+    /// Returns the input passed to the circuit in a test, padded with zeros up to the input
+    /// size if required.
+    fn test_input(&self, input: &ByteString) -> Vec<u8> {
+        if self.pad_test_inputs {
+            zero_padded(input, self.input_size)
+        } else {
+            input.to_vec()
+        }
+    }
+
+    /// Prints the generated code, the `main` function and one Noir test function per test
+    /// case. The test for the i-th case is named after [`test_fn_name`] so that the results
+    /// of `nargo test` can be mapped back to the samples.
+    pub fn print_code(&self, tests: &[NoirTest]) -> String {
         let mut s = String::new();
 
-        match (standard_test, gen_substr_test) {
-            // Handle the standard test case
-            (Some(test_case), None) => {
-                write!(
-                    &mut s,
-                    "{}\nfn main(input: [u8; {}]) {{ regex_match(input); }}\n\n{}\nfn test() {{\n\
-                  let input = {:?};\nregex_match(input);\n\
-                  }}",
-                    self.noir_code,  // Noir code part of `Code`
-                    self.input_size, // Input size for the main function
-                    if should_fail {
-                        "#[test(should_fail)]"
-                    } else {
-                        "#[test]"
-                    },
-                    test_case.as_bytes() // Test case converted to byte array
-                )
-                .unwrap();
-            }
+        write!(
+            &mut s,
+            "{}\nfn main(input: [u8; {}]) {{ regex_match(input); }}\n",
+            self.noir_code,  // Noir code part of `Code`
+            self.input_size, // Input size for the main function
+        )
+        .unwrap();
 
-            // Handle the substring test case
-            (
-                _,
-                Some(InputWithSubstrs {
+        for (index, test) in tests.iter().enumerate() {
+            match test {
+                // Handle the standard test case
+                NoirTest::Standard { input, should_fail } => {
+                    write!(
+                        &mut s,
+                        "\n{}\nfn {}() {{\n\
+                      let input = {:?};\nregex_match(input);\n\
+                      }}\n",
+                        if *should_fail {
+                            "#[test(should_fail)]"
+                        } else {
+                            "#[test]"
+                        },
+                        test_fn_name(index),
+                        self.test_input(input) // Test case converted to byte array
+                    )
+                    .unwrap();
+                }
+
+                // Handle the substring test case
+                NoirTest::Substring(InputWithSubstrs {
                     input: input_byte_array,
                     expected_substrings,
-                }),
-            ) => {
-                write!(
-                    &mut s,
-                    "{}\nfn main(input: [u8; {}]) {{ regex_match(input); }}\n\n#[test]\nfn test() {{\n\
-                  // Input for regex match\n\
-                  let input = {:?};\n\
-                  // This should contain {} substrings\n\
-                  let capture_sequences = regex_match(input);\n\
-                  assert(capture_sequences.len() == {});\n\
-                  let substrings = extract_all_substrings::<_, _, {}>(input, capture_sequences);",
-                    self.noir_code,  // Noir code part of `Code`
-                    self.input_size, // Input size for the main function
-                    input_byte_array.as_bytes(), // Byte array input for the regex
-                    expected_substrings.len(),   // Number of expected substrings
-                    expected_substrings.len(),    // Assertion: number of substrings
-                    self.input_size // Input size reused for max substring length (just inefficient, not insecure)
-                )
-                .unwrap();
-
-                // Iterate over expected substrings and generate assertions
-                for (i, substr) in expected_substrings.iter().enumerate() {
-                    writeln!(s, "let substr{} = substrings.get({});", i, i).unwrap();
-                    for (j, byte) in substr.bytes().enumerate() {
-                        writeln!(s, "assert(substr{}.get({}) == {});", i, j, byte).unwrap();
-                    }
-                    writeln!(s, "assert(substr{}.len() == {});", i, substr.len()).unwrap();
-                }
+                    ..
+                }) => {
+                    write!(
+                        &mut s,
+                        "\n#[test]\nfn {}() {{\n\
+                      // Input for regex match\n\
+                      let input = {:?};\n\
+                      // This should contain {} substrings\n\
+                      let capture_sequences = regex_match(input);\n\
+                      assert(capture_sequences.len() == {});\n\
+                      let substrings = extract_all_substrings::<_, _, {}>(input, capture_sequences);\n",
+                        test_fn_name(index),
+                        self.test_input(input_byte_array), // Byte array input for the regex
+                        expected_substrings.len(),   // Number of expected substrings
+                        expected_substrings.len(),   // Assertion: number of substrings
+                        self.input_size // Input size reused for max substring length (just inefficient, not insecure)
+                    )
+                    .unwrap();
 
-                writeln!(s, "}}").unwrap(); // Close the test function
-            }
+                    // Iterate over expected substrings and generate assertions
+                    for (i, substr) in expected_substrings.iter().enumerate() {
+                        writeln!(s, "let substr{} = substrings.get({});", i, i).unwrap();
+                        for (j, byte) in substr.iter().enumerate() {
+                            writeln!(s, "assert(substr{}.get({}) == {});", i, j, byte).unwrap();
+                        }
+                        writeln!(s, "assert(substr{}.len() == {});", i, substr.len()).unwrap();
+                    }
 
-            // Default case: no test case provided
-            _ => {
-                write!(
-                    &mut s,
-                    "{}\nfn main(input: [u8; {}]) {{ regex_match(input); }}",
-                    self.noir_code, self.input_size,
-                )
-                .unwrap();
+                    writeln!(s, "}}").unwrap(); // Close the test function
+                }
             }
         }
 
@@ -149,39 +178,66 @@ impl Code {
     }
 }
 
+/// Name of the Noir test function generated for the test case in the given position of a batch.
+pub fn test_fn_name(index: usize) -> String {
+    format!("test_{}", index)
+}
+
+/// Writes the transitions of where substrings should be extracted to the JSON file of the workspace.
+fn write_substrs_json<T: Serialize>(transitions: &T, workspace: &Workspace) -> anyhow::Result<()> {
+    let json_file = File::create(workspace.substrs_json_path())?;
+    let mut writer = BufWriter::new(json_file);
+    serde_json::to_writer(&mut writer, transitions)
+        .context("error writing the transitions of raw regex for gen_substrs")?;
+    writer
+        .flush()
+        .context("error flushing the writer to the JSON file")?;
+    Ok(())
+}
+
 /// Function that generates the Noir code associated to a regex.
-fn generate_noir_code(
+pub fn generate_noir_code(
     regex: &RegexInput,
     gen_substrs: bool,
-    result_path: &Path,
+    workspace: &Workspace,
 ) -> anyhow::Result<String> {
+    let result_path = workspace.generation_path();
     let mut command = Command::new("zk-regex");
     match regex {
         RegexInput::Raw(RawRegex::Simple(regex_str)) => {
-            command.args(["raw", "--raw-regex"]).arg(regex_str);
+            command
+                .args(["raw", "--raw-regex"])
+                .arg(transitions::plain_regex(regex_str));
+            // Without explicit transitions, the substrings are the capture groups of the regex
+            if gen_substrs {
+                let transitions = transitions::derive_transitions(regex_str, workspace)
+                    .context("error deriving the substring transitions from the capture groups")?;
+                log::info!(
+                    "derived substring transitions for regex {}: {:?}",
+                    regex_str,
+                    transitions.transitions
+                );
+                write_substrs_json(&transitions, workspace)?;
+                command.arg("-s").arg(workspace.substrs_json_path());
+            }
         }
         RegexInput::Raw(RawRegex::WithTransitions {
             regex: regex_str,
             transitions,
         }) => {
-            command.args(["raw", "--raw-regex"]).arg(regex_str);
+            command
+                .args(["raw", "--raw-regex"])
+                .arg(transitions::plain_regex(regex_str));
             // If substrings should be extracted, add the transitions file
             if gen_substrs {
-                // Write the parts to the JSON file
-                let json_file = File::create(DEFAULT_SUBSTRS_JSON_PATH)?;
-                let mut writer = BufWriter::new(json_file);
-                serde_json::to_writer(&mut writer, transitions)
-                    .context("error writing the transitions of raw regex for gen_substrs")?;
-                writer
-                    .flush()
-                    .context("error flushing the writer to the JSON file")?;
-
-                command.args(["-s", DEFAULT_SUBSTRS_JSON_PATH]);
+                write_substrs_json(transitions, workspace)?;
+                command.arg("-s").arg(workspace.substrs_json_path());
             }
         }
         RegexInput::Decomposed(parts) => {
             // Write the parts to the JSON file
-            let json_file = File::create(DEFAULT_DECOMPOSED_JSON_FILE)?;
+            let decomposed_json_file = workspace.decomposed_json_file();
+            let json_file = File::create(&decomposed_json_file)?;
             let mut writer = BufWriter::new(json_file);
             serde_json::to_writer(&mut writer, &ComponentsWrapper::new(parts.to_vec()))
                 .context("error writing the parts of the decomposed regex")?;
@@ -193,7 +249,7 @@ fn generate_noir_code(
             command
                 .arg("decomposed")
                 .arg("-d")
-                .arg(DEFAULT_DECOMPOSED_JSON_FILE);
+                .arg(decomposed_json_file);
         }
     };
 
@@ -204,7 +260,7 @@ fn generate_noir_code(
 
     let output = command
         .arg("--noir-file-path")
-        .arg(result_path)
+        .arg(&result_path)
         .output()
         .context("error executing the noir generation command")?;
 
@@ -216,7 +272,30 @@ fn generate_noir_code(
 
     // Load code from stored file.
     let noir_generated_code =
-        fs::read_to_string(result_path).context("error writing the noir code into the file")?;
+        fs::read_to_string(&result_path).context("error writing the noir code into the file")?;
 
     Ok(noir_generated_code)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_the_test_inputs_up_to_the_input_size() {
+        let tests = [NoirTest::Standard {
+            input: "ab".into(),
+            should_fail: false,
+        }];
+        let mut code = Code {
+            noir_code: String::new(),
+            input_size: 4,
+            pad_test_inputs: false,
+        };
+        assert!(code.print_code(&tests).contains("let input = [97, 98];"));
+        code.pad_test_inputs = true;
+        assert!(code
+            .print_code(&tests)
+            .contains("let input = [97, 98, 0, 0];"));
+    }
+}
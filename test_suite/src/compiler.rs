@@ -2,7 +2,7 @@ use std::process::Command;
 
 use anyhow::Context;
 
-use crate::constants;
+use crate::workspace::Workspace;
 
 /// Errors that may appear when compiling the Noir code.
 #[derive(thiserror::Error, Debug)]
@@ -12,10 +12,10 @@ pub enum Error {
 }
 
 /// Function that compiles the Noir project.
-pub fn compile_noir_project() -> anyhow::Result<()> {
+pub fn compile_noir_project(workspace: &Workspace) -> anyhow::Result<()> {
     let output = Command::new("nargo")
         .arg("compile")
-        .current_dir(constants::DEFAULT_PROJECT_PATH)
+        .current_dir(workspace.project_path())
         .output()
         .context("error executing the compile command")?;
     if !output.status.success() {
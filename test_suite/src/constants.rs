@@ -8,6 +8,14 @@ pub const DEFAULT_PROJECT_PATH: &str = "./execution_project";
 pub const DEFAULT_GENERATION_PATH: &str = "./noir_code.nr";
 /// Default number of random samples used to test each regex.
 pub const DEFAULT_SAMPLE_NUMBER: usize = 10;
+/// Default number of random samples that are checked against the simulated automaton before
+/// selecting the ones that are tested with Noir.
+pub const DEFAULT_SIMULATED_SAMPLE_NUMBER: usize = 1000;
+/// Maximum number of samples on which the simulated automaton disagrees with the expected
+/// outcome that are tested with Noir.
+pub const DEFAULT_MAX_SIMULATED_DISAGREEMENTS: usize = 100;
+/// Default time limit in seconds for a `nargo test` execution.
+pub const DEFAULT_NARGO_TEST_TIMEOUT_SECS: u64 = 1800;
 /// Default path of the JSON file that stores the decomposed version of a regex.
 pub const DEFAULT_DECOMPOSED_JSON_FILE: &str = "./decomposed.json";
 /// Default path of the JSON file that stored the transitions of where substrings should be extracted
@@ -26,3 +34,35 @@ pub const DEFAULT_WITNESS_PATH: &str = "./target/witness.gz";
 pub const DEFAULT_PROOF_PATH: &str = "./target/proof";
 /// Default Prove.toml path.
 pub const DEFAULT_PROVER_TOML_PATH: &str = "./execution_project/Prover.toml";
+/// Default path for the JSON summary of the run
+pub const DEFAULT_SUMMARY_FILE: &str = "./run_summary.json";
+/// Exit code when at least one test sample failed.
+pub const EXIT_TESTS_FAILED: u8 = 1;
+/// Exit code when a step could not be executed (missing tools, code generation failures, etc.).
+pub const EXIT_INFRASTRUCTURE_ERROR: u8 = 2;
+/// Number of random samples used to derive the substring transitions from the capture groups.
+pub const DEFAULT_DERIVATION_SAMPLE_NUMBER: usize = 10000;
+/// Maximum number of extra repetitions of the repetition operators when sampling the regex to
+/// derive the substring transitions.
+pub const DEFAULT_DERIVATION_MAX_REPEAT: u32 = 8;
+/// Path of the folder with the tests that combine several circuits and can't be generated from the database.
+pub const DEFAULT_HARDCODED_TESTS_PATH: &str = "./hardcoded_tests";
+/// Default number of mutants of the passing samples that are checked against the simulated
+/// automaton before selecting the ones that are tested with Noir.
+pub const DEFAULT_MUTANT_NUMBER: usize = 1000;
+/// Seed of the random samples used to derive the substring transitions from the capture groups.
+pub const DERIVATION_SEED: u64 = 0;
+/// Maximum number of `nargo test` runs used to shrink a failing sample.
+pub const DEFAULT_SHRINK_ROUNDS: usize = 32;
+/// Maximum number of smaller inputs tested in each round of the shrinking of a failing sample.
+pub const DEFAULT_SHRINK_CANDIDATES: usize = 64;
+/// Maximum number of inputs aimed at the transitions of the automaton that the other samples
+/// don't take.
+pub const DEFAULT_COVERAGE_SAMPLE_NUMBER: usize = 100;
+/// Maximum number of random characters added after a sample in the trailing garbage variant of
+/// the padding tests.
+pub const DEFAULT_GARBAGE_LEN: usize = 8;
+
+/// Input size of the entries imported from the circom tests, which is the input size used by
+/// the circom entries of the database.
+pub const DEFAULT_IMPORT_INPUT_SIZE: usize = 1024;
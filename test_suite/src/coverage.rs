@@ -0,0 +1,265 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use regex::Regex;
+
+use crate::{circom_import, db::DbEntry, report::entry_display_name};
+
+/// Circom test case, identified by its name together with the file and the circuit it belongs
+/// to, since the same name is used in several files and for several circuits of a file.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CircomTestCase {
+    /// Circom test file of the test case, e.g. `asterisk.test.js`. Unknown for the names read
+    /// from a text file.
+    pub file: Option<String>,
+    /// `circuit_name` of the circuit tested by the test case, when the circuit is generated from
+    /// a decomposed regex.
+    pub circuit: Option<String>,
+    /// Name given to `it`, e.g. `asterisk1 valid case 1`.
+    pub name: String,
+}
+
+impl CircomTestCase {
+    /// Whether the entry implements the test case: the entry has a sample labeled with the name
+    /// of the test case and the entry is the one of its circuit, or the one of its file if the
+    /// circuit is unknown or the entry has no `circuit_name`. Without file, only the name is
+    /// compared.
+    fn is_implemented_by(&self, entry: &DbEntry) -> bool {
+        if !entry
+            .circom_testnames()
+            .any(|testname| testname == self.name)
+        {
+            return false;
+        }
+        let same_circuit = self.circuit.is_some() && entry.circuit_name == self.circuit;
+        let same_file = self.file.is_some() && entry.test_name == self.file;
+        match (&self.file, &self.circuit) {
+            (None, None) => true,
+            (_, Some(_)) if entry.circuit_name.is_some() => same_circuit,
+            _ => same_file,
+        }
+    }
+}
+
+impl Display for CircomTestCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, &self.circuit) {
+            (_, Some(circuit)) => write!(f, "{} {:?}", circuit, self.name),
+            (Some(file), None) => write!(f, "{} {:?}", file, self.name),
+            (None, None) => write!(f, "{:?}", self.name),
+        }
+    }
+}
+
+/// Coverage of a list of circom test cases by the tests of this repository.
+pub struct CoverageReport {
+    /// Test cases implemented by the database, with the entries that implement them.
+    automated: BTreeMap<CircomTestCase, Vec<String>>,
+    /// Test cases implemented in `hardcoded_tests`, with the files that implement them.
+    hardcoded: BTreeMap<CircomTestCase, Vec<String>>,
+    /// Test cases that are not implemented.
+    missing: Vec<CircomTestCase>,
+}
+
+impl CoverageReport {
+    /// Looks up each circom test case in the entries of the database first and then in the
+    /// hardcoded tests, which are found by name.
+    pub fn new(
+        circom_test_cases: &[CircomTestCase],
+        db_entries: &[DbEntry],
+        hardcoded_testnames: &BTreeMap<String, Vec<String>>,
+    ) -> Self {
+        let mut report = Self {
+            automated: BTreeMap::new(),
+            hardcoded: BTreeMap::new(),
+            missing: Vec::new(),
+        };
+        for test_case in circom_test_cases {
+            let entries: Vec<String> = db_entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| test_case.is_implemented_by(entry))
+                .map(|(index, entry)| entry_display_name(entry.name(), index))
+                .collect();
+            if !entries.is_empty() {
+                report.automated.insert(test_case.clone(), entries);
+            } else if let Some(files) = hardcoded_testnames.get(&test_case.name) {
+                report.hardcoded.insert(test_case.clone(), files.clone());
+            } else {
+                report.missing.push(test_case.clone());
+            }
+        }
+        report
+    }
+
+    /// Whether all the circom test cases are implemented.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "automated ({}):", self.automated.len())?;
+        for (test_case, entries) in &self.automated {
+            writeln!(f, "  {}: {}", test_case, entries.join(", "))?;
+        }
+        writeln!(f, "hardcoded ({}):", self.hardcoded.len())?;
+        for (test_case, files) in &self.hardcoded {
+            writeln!(f, "  {}: {}", test_case, files.join(", "))?;
+        }
+        writeln!(f, "missing ({}):", self.missing.len())?;
+        for test_case in &self.missing {
+            writeln!(f, "  {}", test_case)?;
+        }
+        write!(
+            f,
+            "{} automated, {} hardcoded, {} missing",
+            self.automated.len(),
+            self.hardcoded.len(),
+            self.missing.len()
+        )
+    }
+}
+
+/// Reads the circom test cases of a file. For `.js` files, the names are the descriptions of
+/// the `it(...)` and `test(...)` calls, and the circuits are found as in
+/// [`import_test_file`](circom_import::import_test_file); other files contain one name per
+/// line, without file or circuit.
+pub fn read_circom_test_cases(path: &Path) -> anyhow::Result<Vec<CircomTestCase>> {
+    let contents =
+        fs::read_to_string(path).context(format!("error reading the test names in {:?}", path))?;
+    if path.extension().is_some_and(|extension| extension == "js") {
+        let file = path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string());
+        Ok(circom_import::test_case_circuits(&contents, path)?
+            .into_iter()
+            .map(|(name, circuit)| CircomTestCase {
+                file: file.clone(),
+                circuit,
+                name,
+            })
+            .collect())
+    } else {
+        Ok(contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|name| CircomTestCase {
+                file: None,
+                circuit: None,
+                name: name.to_string(),
+            })
+            .collect())
+    }
+}
+
+/// Finds the circom test cases implemented in the hardcoded tests. Each Noir test function
+/// names the circom test case in a comment, as in `fn test_valid_1() { // "case name"`.
+pub fn hardcoded_testnames(path: &Path) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+    let test_fn_regex = Regex::new(r#"fn \w+\(\)\s*\{\s*//\s*"(.*)""#)?;
+    let mut testnames: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for file in
+        noir_files(path).context(format!("error reading the hardcoded tests in {:?}", path))?
+    {
+        let contents = fs::read_to_string(&file)
+            .context(format!("error reading the hardcoded test {:?}", file))?;
+        let file_name = file
+            .strip_prefix(path)
+            .unwrap_or(&file)
+            .display()
+            .to_string();
+        for captures in test_fn_regex.captures_iter(&contents) {
+            testnames
+                .entry(captures[1].to_string())
+                .or_default()
+                .push(file_name.clone());
+        }
+    }
+    Ok(testnames)
+}
+
+/// Lists the Noir files in a folder recursively, skipping the compilation artifacts.
+fn noir_files(path: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_name() == "target" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            files.extend(noir_files(&entry.path())?);
+        } else if entry
+            .path()
+            .extension()
+            .is_some_and(|extension| extension == "nr")
+        {
+            files.push(entry.path());
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASTERISK_TESTS: &str = r#"
+    circuit1 = await wasm_tester(path.join(__dirname, "./circuits/test_asterisk1_regex.circom"), option);
+    circuit2 = await wasm_tester(path.join(__dirname, "./circuits/test_asterisk2_regex.circom"), option);
+    writeFileSync(
+      path.join(__dirname, "./circuits/test_asterisk1_regex.circom"),
+      compiler.genFromDecomposed(readFileSync(path.join(__dirname, "./circuits/asterisk1.json"), "utf8"), {})
+    );
+    writeFileSync(
+      path.join(__dirname, "./circuits/test_asterisk2_regex.circom"),
+      compiler.genFromDecomposed(readFileSync(path.join(__dirname, "./circuits/asterisk2.json"), "utf8"), {})
+    );
+    it("asterisk1 invalid case 1", async () => {
+      const witness = await circuit1.calculateWitness({ msg: input });
+    });
+    it("asterisk1 invalid case 1", async () => {
+      const witness = await circuit2.calculateWitness({ msg: input });
+    });
+    "#;
+
+    fn entry(circuit_name: &str, circom_testnames_fails: &[&str]) -> DbEntry {
+        serde_json::from_value(serde_json::json!({
+            "circuit_name": circuit_name,
+            "regex": {"raw": "x"},
+            "input_size": 16,
+            "samples_pass": [],
+            "samples_fail": circom_testnames_fails.iter().map(|_| "a").collect::<Vec<_>>(),
+            "circom_testnames_fails": circom_testnames_fails,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_the_test_cases_by_circuit() {
+        let test_cases: Vec<CircomTestCase> =
+            circom_import::test_case_circuits(ASTERISK_TESTS, Path::new("asterisk.test.js"))
+                .unwrap()
+                .into_iter()
+                .map(|(name, circuit)| CircomTestCase {
+                    file: Some("asterisk.test.js".to_string()),
+                    circuit,
+                    name,
+                })
+                .collect();
+        let entries = [
+            entry("asterisk1", &["asterisk1 invalid case 1"]),
+            entry("asterisk2", &[]),
+        ];
+        let report = CoverageReport::new(&test_cases, &entries, &BTreeMap::new());
+        assert_eq!(report.automated.len(), 1);
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.missing[0].circuit.as_deref(), Some("asterisk2"));
+    }
+}
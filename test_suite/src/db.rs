@@ -1,21 +1,71 @@
-use std::{fs, path::Path};
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
 
 use anyhow::Context;
+use jsonschema::error::ValidationErrorKind;
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::{bytes::ByteString, oracle::MatchMode};
+
+/// Errors that can arise when loading the database.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The database doesn't follow the schema. Contains a message for each violation, with the
+    /// file and the JSON pointer of the value that causes it.
+    #[error("the database doesn't follow the schema:\n{}", .0.join("\n"))]
+    SchemaViolation(Vec<String>),
+}
+
 /// Database of regular expressions that will be tested.
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct RegexDb {
-    // Regex entries in the database
-    db_entries: Vec<DbEntry>,
-    // Defines wether we need to benchmark all the entries in the database.
+    /// Regex entries in the database.
+    #[serde(default)]
+    database: Vec<DbEntry>,
+    /// Glob patterns of other database files whose entries are added after the entries of this
+    /// file, relative to this file, e.g. `email/*.yaml`. Only the `bench_all` of the loaded file
+    /// is used.
+    #[serde(default)]
+    include: Vec<String>,
+    /// Defines wether we need to benchmark all the entries in the database.
     #[serde(default)]
     pub bench_all: bool,
 }
 
+/// File and position of an entry of the database.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct EntrySource {
+    /// Database file that contains the entry.
+    pub file: PathBuf,
+    /// Position of the entry in the `database` array of the file.
+    pub index: usize,
+}
+
+impl EntrySource {
+    /// Whether both sources are the same position of the same file, even if the paths of the
+    /// file are written differently.
+    pub fn is_same_entry(&self, other: &EntrySource) -> bool {
+        self.index == other.index
+            && fs::canonicalize(&self.file).unwrap_or(self.file.clone())
+                == fs::canonicalize(&other.file).unwrap_or(other.file.clone())
+    }
+}
+
+impl Display for EntrySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#/database/{}", self.file.display(), self.index)
+    }
+}
+
 /// Represents each fragment in a decomposed regex.
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct RegexFragment {
     /// Determines if this part of the regex is private.
     pub is_public: bool,
@@ -23,8 +73,8 @@ pub struct RegexFragment {
     pub regex_def: String,
 }
 
-// Represents the input regex in the database.
-#[derive(Deserialize, Serialize)]
+/// Represents the input regex in the database.
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum RegexInput {
     /// A raw regex with optional transitions.
@@ -33,8 +83,8 @@ pub enum RegexInput {
     Decomposed(Vec<RegexFragment>),
 }
 
-// `RawRegex` can either be a simple string or an object with transitions.
-#[derive(Deserialize, Serialize, Debug)]
+/// A raw regex, either as a simple string or as an object with transitions.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(untagged)] // Allows deserialization of either a string or a structured object.
 pub enum RawRegex {
     /// Simple string form for raw regex.
@@ -58,8 +108,8 @@ impl RawRegex {
     }
 }
 
-// Struct representing the transitions.
-#[derive(Deserialize, Serialize, Debug)]
+/// Transitions `[s, s_next]` of the automaton where each substring is extracted.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 pub struct Transitions {
     /// Transitions data.
     pub transitions: Vec<Vec<Vec<u32>>>,
@@ -80,70 +130,317 @@ impl RegexInput {
     }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+/// Samples that should match the regex, with or without expected substrings.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(untagged)] // Automatically distinguish between the two formats
 pub enum SamplesPass {
     /// For cases with substrings (complex structure)
     WithSubstrs(Vec<InputWithSubstrs>),
     /// For cases without substrings (simple structure)
-    WithoutSubstrs(Vec<String>),
+    WithoutSubstrs(Vec<ByteString>),
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+/// Sample that should match the regex together with the substrings extracted from it.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct InputWithSubstrs {
     /// The input string
-    pub input: String,
+    pub input: ByteString,
     /// The expected substrings from the input
-    pub expected_substrings: Vec<String>,
+    pub expected_substrings: Vec<ByteString>,
+    /// Name of the circom test case that uses this input, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circom_testname: Option<String>,
+}
+
+/// Annotation of an entry that is known to fail, e.g. because of a limitation of zk-regex.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ExpectedFailure {
+    /// Why the entry fails.
+    pub reason: String,
+    /// Given samples that are known to be judged incorrectly. The other samples must still be
+    /// judged correctly. If there are none, any failure of the entry is expected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<ByteString>,
+}
+
+/// Annotation of an entry that is neither tested nor benchmarked.
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Skip {
+    /// Why the entry is skipped.
+    pub reason: String,
 }
 
 /// An entry of the test database.
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct DbEntry {
+    /// Name of the circom circuit this entry relates to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub circuit_name: Option<String>,
+    /// Name of the circom test file this entry relates to, e.g. `simple_regex.test.js`. It
+    /// identifies the entry when there is no specific circuit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_name: Option<String>,
+    /// Tags used to select groups of entries in a run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     /// The regex of the entry.
     pub regex: RegexInput,
+    /// Rule used to decide whether the random samples should match the regex. Default `search`.
+    #[serde(default, skip_serializing_if = "MatchMode::is_search")]
+    pub match_mode: MatchMode,
+    /// Seed of the random samples of the entry. It takes precedence over the `--seed` option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
     /// Whether substrings should be generated. Default false
     #[serde(default)]
     pub gen_substrs: bool,
     /// The maximum input size to generate random regexes for testing.
     pub input_size: usize,
+    /// Input sizes at which the entry is tested and benchmarked instead of `input_size`. It is
+    /// overridden by the `--sizes` option.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_sizes: Vec<usize>,
+    /// Number of random samples tested for the entry. It is overridden by the
+    /// `--random-samples` option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_samples: Option<usize>,
     /// Samples that are provided as input by the user and expected to pass the regex
     pub samples_pass: SamplesPass,
     /// Samples that are provided as input by the user and *not* expected to pass the regex.
-    pub samples_fail: Vec<String>,
+    pub samples_fail: Vec<ByteString>,
+    /// Names of the circom test cases that use the failing samples.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub circom_testnames_fails: Vec<String>,
+    /// Marks the entry, or some of its samples, as known to fail. The failures are reported as
+    /// expected and don't fail the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_failure: Option<ExpectedFailure>,
+    /// Marks the entry as not processed in the runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skip: Option<Skip>,
     /// Defines wether you want a benchmark for the regex in the given test
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub with_bench: bool,
     /// String used for the benchmarking. If you want to benchmark, this field is mandatory.
     /// If you want just testing, this field is optional.
-    #[serde(default)]
-    pub benchmark_str: String,
+    #[serde(default, skip_serializing_if = "ByteString::is_empty")]
+    pub benchmark_str: ByteString,
+    /// File and position of the entry, set when the database is loaded.
+    #[serde(skip)]
+    pub source: EntrySource,
+    /// Whether the inputs of the tests are padded with zeros up to the input size, which is set
+    /// for the sizes of a sweep.
+    #[serde(skip)]
+    pub pad_test_inputs: bool,
+}
+
+impl DbEntry {
+    /// Returns the name that identifies the entry in the reports: the `circuit_name`, or the
+    /// `test_name` if there is no circuit name.
+    pub fn name(&self) -> Option<&str> {
+        self.circuit_name.as_deref().or(self.test_name.as_deref())
+    }
+
+    /// Returns the input sizes at which the entry is processed: the given sizes, the
+    /// `input_sizes` of the entry, or its `input_size`.
+    pub fn sizes(&self, sizes: &[usize]) -> Vec<usize> {
+        if !sizes.is_empty() {
+            sizes.to_vec()
+        } else if !self.input_sizes.is_empty() {
+            self.input_sizes.clone()
+        } else {
+            vec![self.input_size]
+        }
+    }
+
+    /// Returns the entries that are processed for this entry: the entry itself, or a copy for
+    /// each size if the entry is swept over the given sizes or its `input_sizes`.
+    pub fn sweep(&self, sizes: &[usize]) -> Vec<Self> {
+        if sizes.is_empty() && self.input_sizes.is_empty() {
+            vec![self.clone()]
+        } else {
+            self.sizes(sizes)
+                .into_iter()
+                .map(|size| self.with_input_size(size))
+                .collect()
+        }
+    }
+
+    /// Returns a copy of the entry for one of the sizes of a sweep. The inputs of the tests are
+    /// padded with zeros up to the size, so that the tests run the circuit at that size, and so
+    /// is the benchmarking string, if any. A longer benchmarking string is left as is and
+    /// reported when the entry is benchmarked.
+    fn with_input_size(&self, input_size: usize) -> Self {
+        let mut entry = self.clone();
+        entry.input_size = input_size;
+        entry.pad_test_inputs = true;
+        if !entry.benchmark_str.is_empty() && entry.benchmark_str.len() < input_size {
+            let mut benchmark_bytes = entry.benchmark_str.to_vec();
+            benchmark_bytes.resize(input_size, 0);
+            entry.benchmark_str = benchmark_bytes.into();
+        }
+        entry
+    }
+
+    /// Returns the inputs of the given samples that should pass.
+    pub fn pass_inputs(&self) -> Vec<&ByteString> {
+        match &self.samples_pass {
+            SamplesPass::WithSubstrs(samples) => {
+                samples.iter().map(|sample| &sample.input).collect()
+            }
+            SamplesPass::WithoutSubstrs(samples) => samples.iter().collect(),
+        }
+    }
+
+    /// Returns the names of the circom test cases implemented by the samples of the entry.
+    pub fn circom_testnames(&self) -> impl Iterator<Item = &str> {
+        let pass_testnames = match &self.samples_pass {
+            SamplesPass::WithSubstrs(samples) => samples
+                .iter()
+                .filter_map(|sample| sample.circom_testname.as_deref())
+                .collect(),
+            SamplesPass::WithoutSubstrs(_) => Vec::new(),
+        };
+        pass_testnames
+            .into_iter()
+            .chain(self.circom_testnames_fails.iter().map(String::as_str))
+    }
 }
 
 impl RegexDb {
-    /// Constructs a database from a JSON file where the file contains the information in the following format:
+    /// Constructs a database from a file where the file contains the information in the following format:
+    /// `{ "bench_all": <bool>, "include": [<pattern>, ...], "database": [<entry>, ...] }`. The
+    /// file can be written in JSON, YAML (`.yaml` or `.yml`) or TOML (`.toml`). The entries of
+    /// the included files are added after the entries of the file, and each file is loaded only
+    /// once. Every file is validated against the [schema](RegexDb::schema) first, so that every
+    /// problem is reported with its location.
     pub fn load_from_file(file_path: &Path) -> anyhow::Result<Self> {
-        let file_regex_content =
-            fs::read_to_string(file_path).context("error reading the regex content")?;
-        let json_value: Value =
-            serde_json::from_str(&file_regex_content).context("error parsing the json")?;
-        let regex_db: Vec<DbEntry> = serde_json::from_str(&json_value["database"].to_string())
-            .context("error parsing the database array")?;
-
-        // If the field in the "bench_all" is a boolean, take the boolean,
-        // otherwhise, use false as a default.
-        let bench_all: bool = json_value["bench_all"].as_bool().unwrap_or(false);
+        let mut loaded_files = HashSet::new();
+        let mut db = Self::load_file(file_path, &mut loaded_files)?;
+        let includes = std::mem::take(&mut db.include);
+        db.database.extend(Self::load_includes(
+            file_path,
+            &includes,
+            &mut loaded_files,
+        )?);
+        db.include = includes;
+        Ok(db)
+    }
 
-        let mut regexes = Vec::new();
+    /// Loads a single database file without its includes and sets the source of its entries.
+    fn load_file(file_path: &Path, loaded_files: &mut HashSet<PathBuf>) -> anyhow::Result<Self> {
+        loaded_files.insert(fs::canonicalize(file_path).unwrap_or(file_path.to_path_buf()));
+        let file_regex_content = fs::read_to_string(file_path)
+            .context(format!("error reading the database file {:?}", file_path))?;
+        let extension = file_path
+            .extension()
+            .and_then(|extension| extension.to_str());
+        let json_value: Value = match extension {
+            Some("yaml" | "yml") => serde_norway::from_str(&file_regex_content)
+                .context(format!("error parsing the YAML file {:?}", file_path))?,
+            Some("toml") => toml::from_str(&file_regex_content)
+                .context(format!("error parsing the TOML file {:?}", file_path))?,
+            _ => serde_json::from_str(&file_regex_content)
+                .context(format!("error parsing the json file {:?}", file_path))?,
+        };
+        Self::validate(file_path, &json_value)?;
+        let mut db: Self = serde_json::from_value(json_value)
+            .context(format!("error parsing the database file {:?}", file_path))?;
+        for (index, entry) in db.database.iter_mut().enumerate() {
+            entry.source = EntrySource {
+                file: file_path.to_path_buf(),
+                index,
+            };
+        }
+        Ok(db)
+    }
 
-        for db_element in regex_db {
-            regexes.push(db_element);
+    /// Loads the entries of the files that match the include patterns of a database file,
+    /// together with the entries of their own includes. The files of each pattern are loaded in
+    /// alphabetical order.
+    fn load_includes(
+        file_path: &Path,
+        includes: &[String],
+        loaded_files: &mut HashSet<PathBuf>,
+    ) -> anyhow::Result<Vec<DbEntry>> {
+        let dir = file_path.parent().unwrap_or(Path::new("."));
+        let mut entries = Vec::new();
+        for pattern in includes {
+            let full_pattern = dir.join(pattern);
+            let mut included_files = glob::glob(&full_pattern.to_string_lossy())
+                .context(format!("invalid include pattern {:?}", pattern))?
+                .collect::<Result<Vec<_>, _>>()
+                .context(format!(
+                    "error reading the files of the pattern {:?}",
+                    pattern
+                ))?;
+            included_files.sort();
+            if included_files.is_empty() {
+                log::warn!(
+                    "the include pattern {:?} of {:?} doesn't match any file",
+                    pattern,
+                    file_path
+                );
+            }
+            for included_file in included_files {
+                let canonical_path =
+                    fs::canonicalize(&included_file).unwrap_or(included_file.clone());
+                if loaded_files.contains(&canonical_path) {
+                    log::warn!(
+                        "the file {:?} is already loaded, so it is not included again by {:?}",
+                        included_file,
+                        file_path
+                    );
+                    continue;
+                }
+                let included_db = Self::load_file(&included_file, loaded_files)?;
+                entries.extend(included_db.database);
+                entries.extend(Self::load_includes(
+                    &included_file,
+                    &included_db.include,
+                    loaded_files,
+                )?);
+            }
         }
+        Ok(entries)
+    }
 
-        Ok(Self {
-            db_entries: regexes,
-            bench_all,
-        })
+    /// Returns the JSON Schema of the database files.
+    pub fn schema() -> RootSchema {
+        schema_for!(RegexDb)
+    }
+
+    /// Validates a database file against the schema.
+    fn validate(file_path: &Path, json_value: &Value) -> anyhow::Result<()> {
+        let schema =
+            serde_json::to_value(Self::schema()).context("error serializing the schema")?;
+        let validator = jsonschema::JSONSchema::compile(&schema)
+            .map_err(|err| anyhow::anyhow!("error compiling the schema: {}", err))?;
+        if let Err(errors) = validator.validate(json_value) {
+            anyhow::bail!(Error::SchemaViolation(
+                errors
+                    .map(|err| {
+                        let location = format!("{}#{}", file_path.display(), err.instance_path);
+                        match err.kind {
+                            // The default message of these errors contains the whole value,
+                            // which can be a long list of samples
+                            ValidationErrorKind::AnyOf | ValidationErrorKind::OneOfNotValid => {
+                                format!(
+                                    "{}: the value doesn't have any of the allowed forms",
+                                    location
+                                )
+                            }
+                            _ => format!("{}: {}", location, err),
+                        }
+                    })
+                    .collect()
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -152,7 +449,7 @@ impl IntoIterator for RegexDb {
     type IntoIter = std::vec::IntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.db_entries.into_iter()
+        self.database.into_iter()
     }
 }
 
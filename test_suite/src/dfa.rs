@@ -0,0 +1,378 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    ops::Range,
+};
+
+use regex::Regex;
+
+use crate::bytes::ByteString;
+
+/// Errors that can arise when reading the automaton from the generated Noir code.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The generated code doesn't contain the expected element of the automaton.
+    #[error("error parsing the automaton from the noir code: {0}")]
+    ParseFailed(&'static str),
+}
+
+/// Automaton stored in the lookup table of the Noir code generated by zk-regex.
+///
+/// The table is written as `table[s * 256 + c] = t` lines inside `make_lookup_table`, where
+/// a transition to the state 0 means that there is no transition.
+pub struct Dfa {
+    /// Transition table indexed by `s * 256 + c`.
+    table: Vec<u32>,
+    /// State in which the automaton starts.
+    initial_state: u32,
+    /// States in which the input is accepted.
+    accepting_states: HashSet<u32>,
+    /// Transitions `(s, s_next)` that add the current byte to a substring.
+    substring_transitions: HashSet<(u32, u32)>,
+    /// Transitions `(s, s_next)` that complete the match, after which the input is not read
+    /// anymore.
+    complete_transitions: HashSet<(u32, u32)>,
+}
+
+/// Result of running an input through the automaton.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DfaRun {
+    /// Whether the automaton ends in an accepting state.
+    pub accepted: bool,
+    /// Positions of the extracted substrings in the input.
+    pub substrings: Vec<Range<usize>>,
+    /// Transitions `(s, s_next)` taken while reading the input, without the resets.
+    pub steps: Vec<(u32, u32)>,
+    /// State in which the automaton ends.
+    pub final_state: u32,
+}
+
+impl DfaRun {
+    /// Returns the extracted substrings of the given input.
+    pub fn substrings_of<'a>(&self, input: &'a [u8]) -> Vec<&'a [u8]> {
+        self.substrings
+            .iter()
+            .map(|range| &input[range.clone()])
+            .collect()
+    }
+}
+
+impl Dfa {
+    /// Reads the automaton from the code returned by the zk-regex tool.
+    pub fn from_noir_code(noir_code: &str) -> anyhow::Result<Self> {
+        let entry_regex = Regex::new(r"table\[(\d+) \* 256 \+ (\d+)\] = (\d+);")?;
+        let state_regex = Regex::new(r"\(s == (\d+)\)")?;
+        let transition_regex = Regex::new(r"\(s == (\d+)\) & \(s_next == (\d+)\)")?;
+
+        let mut table = Vec::new();
+        for captures in entry_regex.captures_iter(noir_code) {
+            let state: usize = captures[1].parse()?;
+            let byte: usize = captures[2].parse()?;
+            let next_state: u32 = captures[3].parse()?;
+            let index = state * 256 + byte;
+            if index >= table.len() {
+                table.resize((state + 1) * 256, 0);
+            }
+            table[index] = next_state;
+        }
+        if table.is_empty() {
+            anyhow::bail!(Error::ParseFailed("the lookup table has no transitions"));
+        }
+
+        // The start anchor `^` is implemented as a transition with the byte 255 from the
+        // state 0 that is taken before reading the input.
+        let initial_state = if noir_code.contains("s = table[255];") {
+            table[255]
+        } else {
+            0
+        };
+
+        // The final check is `assert((s == a) | (s == b), ...)`, which may be commented out
+        // in hand-modified code.
+        let accepting_states = noir_code
+            .lines()
+            .find(|line| line.contains("assert((s =="))
+            .ok_or(Error::ParseFailed("the final state check was not found"))?;
+        let accepting_states = state_regex
+            .captures_iter(accepting_states)
+            .map(|captures| captures[1].parse())
+            .collect::<Result<HashSet<u32>, _>>()?;
+
+        // The substring transitions are listed in the `if` below the "Fill up substrings" comment.
+        let substring_transitions = match noir_code.split_once("// Fill up substrings") {
+            Some((_, substring_code)) => {
+                let condition = substring_code.split('{').next().unwrap_or_default();
+                transition_regex
+                    .captures_iter(condition)
+                    .map(|captures| Ok((captures[1].parse()?, captures[2].parse()?)))
+                    .collect::<anyhow::Result<HashSet<(u32, u32)>>>()?
+            }
+            None => HashSet::new(),
+        };
+
+        // The transitions that complete the match are in the `else if` that sets `complete`,
+        // which is followed by a `break` at the end of the loop.
+        let complete_transitions = match noir_code.split_once("complete = true;") {
+            Some((before_complete, _)) => {
+                let condition = before_complete.rsplit("else if").next().unwrap_or_default();
+                transition_regex
+                    .captures_iter(condition)
+                    .map(|captures| Ok((captures[1].parse()?, captures[2].parse()?)))
+                    .collect::<anyhow::Result<HashSet<(u32, u32)>>>()?
+            }
+            None => HashSet::new(),
+        };
+
+        Ok(Self {
+            table,
+            initial_state,
+            accepting_states,
+            substring_transitions,
+            complete_transitions,
+        })
+    }
+
+    /// Returns the next state from the given state and byte.
+    fn transition(&self, state: u32, byte: u8) -> u32 {
+        self.table
+            .get(state as usize * 256 + byte as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Returns the transitions `(s, s_next)` taken for each byte of the input when it is read
+    /// from the initial state, or `None` if the automaton has to reset before the end.
+    pub fn trace(&self, input: &[u8]) -> Option<Vec<(u32, u32)>> {
+        let mut s = self.initial_state;
+        let mut steps = Vec::with_capacity(input.len());
+        for &byte in input {
+            let s_next = self.transition(s, byte);
+            if s_next == 0 {
+                return None;
+            }
+            steps.push((s, s_next));
+            s = s_next;
+        }
+        Some(steps)
+    }
+
+    /// Runs the input through the automaton in the same way as `__regex_match` does in the
+    /// generated Noir code.
+    pub fn run(&self, input: &[u8]) -> DfaRun {
+        let mut substrings = Vec::new();
+        let mut current_substring: Option<Range<usize>> = None;
+        let mut steps = Vec::new();
+        let mut s = self.initial_state;
+
+        for (i, &byte) in input.iter().enumerate() {
+            let mut s_next = self.transition(s, byte);
+            let reset = s_next == 0;
+            if reset {
+                // Check if there is any transition that could be done from a "restart"
+                s = 0;
+                s_next = self.transition(0, byte);
+                // A substring in the making is invalid after a reset
+                current_substring = None;
+            }
+
+            let mut complete = false;
+            if self.substring_transitions.contains(&(s, s_next)) {
+                current_substring.get_or_insert(i..i).end = i + 1;
+            } else if current_substring.is_some() && s_next == 0 {
+                // The substrings found so far are discarded as well
+                current_substring = None;
+                substrings.clear();
+            } else if self.complete_transitions.contains(&(s, s_next)) {
+                complete = true;
+            } else if let Some(substring) = current_substring.take() {
+                // The substring is done so "save" it
+                substrings.push(substring);
+            }
+            if s_next != 0 {
+                steps.push((s, s_next));
+            }
+            s = s_next;
+            // The rest of the input is not read once the match is complete
+            if complete {
+                break;
+            }
+        }
+        // Add pending substring that hasn't been added
+        if let Some(substring) = current_substring {
+            substrings.push(substring);
+        }
+
+        DfaRun {
+            accepted: self.accepting_states.contains(&s),
+            substrings,
+            steps,
+            final_state: s,
+        }
+    }
+
+    /// Returns the entries `(s, c, s_next)` of the lookup table that are transitions. With the
+    /// start anchor, the entry of the byte 255 from the state 0 is only used to enter the
+    /// initial state, so it is not included.
+    fn entries(&self) -> impl Iterator<Item = (u32, u8, u32)> + '_ {
+        self.table
+            .iter()
+            .enumerate()
+            .filter(|&(_, &s_next)| s_next != 0)
+            .map(|(index, &s_next)| ((index / 256) as u32, (index % 256) as u8, s_next))
+            .filter(|&(s, c, _)| !(s == 0 && c == 255 && self.initial_state != 0))
+    }
+
+    /// Returns all the states of the automaton.
+    pub fn states(&self) -> BTreeSet<u32> {
+        self.entries()
+            .flat_map(|(s, _, s_next)| [s, s_next])
+            .chain([self.initial_state])
+            .collect()
+    }
+
+    /// Returns all the transitions `(s, s_next)` of the automaton.
+    pub fn transitions(&self) -> BTreeSet<(u32, u32)> {
+        self.entries().map(|(s, _, s_next)| (s, s_next)).collect()
+    }
+
+    /// Returns the accepting states of the automaton.
+    pub fn accepting_states(&self) -> BTreeSet<u32> {
+        self.accepting_states.iter().copied().collect()
+    }
+
+    /// Builds a short ASCII input that takes the given transition: the shortest path from the
+    /// initial state to `s`, a byte from `s` to `s_next`, and the shortest path from `s_next` to
+    /// an accepting state, if there is one. Printable characters are preferred. Returns `None`
+    /// if the transition can't be reached with ASCII characters.
+    pub fn input_through(&self, (s, s_next): (u32, u32)) -> Option<ByteString> {
+        let mut input = self.shortest_path(self.initial_state, |state| state == s)?;
+        input.push(
+            ascii_bytes()
+                .find(|&byte| self.transition(s, byte) == s_next)
+                .filter(|_| s_next != 0)?,
+        );
+        if let Some(suffix) =
+            self.shortest_path(s_next, |state| self.accepting_states.contains(&state))
+        {
+            input.extend(suffix);
+        }
+        Some(input.into())
+    }
+
+    /// Returns the shortest ASCII input that goes from the state `from` to a state for which
+    /// `is_target` holds, without resetting the automaton.
+    fn shortest_path(&self, from: u32, is_target: impl Fn(u32) -> bool) -> Option<Vec<u8>> {
+        // Previous state and byte of each state reached by the breadth-first search
+        let mut parents: HashMap<u32, Option<(u32, u8)>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(state) = queue.pop_front() {
+            if is_target(state) {
+                let mut path = Vec::new();
+                let mut current = state;
+                while let Some(&Some((previous, byte))) = parents.get(&current) {
+                    path.push(byte);
+                    current = previous;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for byte in ascii_bytes() {
+                let next_state = self.transition(state, byte);
+                if next_state != 0 && !parents.contains_key(&next_state) {
+                    parents.insert(next_state, Some((state, byte)));
+                    queue.push_back(next_state);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// ASCII bytes with the printable characters first.
+fn ascii_bytes() -> impl Iterator<Item = u8> {
+    (b' '..=b'~').chain(0..b' ').chain([0x7f])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parts of the code generated by zk-regex for `(\r\n|^)a=([xy]+);`, which extracts the
+    /// value of `a`. The transitions from the accepting state 6 are cut down to the byte `\r`.
+    const CODE: &str = r#"
+comptime fn make_lookup_table() -> [Field; 2048] {
+    let mut table = [0; 2048];
+    table[0 * 256 + 255] = 1;
+    table[0 * 256 + 13] = 2;
+    table[2 * 256 + 10] = 1;
+    table[1 * 256 + 97] = 3;
+    table[3 * 256 + 61] = 4;
+    table[4 * 256 + 120] = 5;
+    table[4 * 256 + 121] = 5;
+    table[5 * 256 + 120] = 5;
+    table[5 * 256 + 121] = 5;
+    table[5 * 256 + 59] = 6;
+    table[6 * 256 + 13] = 7;
+    table[7 * 256 + 13] = 7;
+    table
+}
+
+pub unconstrained fn __regex_match<let N: u32>(input: [u8; N]) -> BoundedVec<Sequence, 1> {
+    s = table[255];
+    for i in 0..input.len() {
+        // Fill up substrings
+        if ((s == 4) & (s_next == 5) | (s == 5) & (s_next == 5)) {
+            consecutive_substr = 1;
+        } else if ((consecutive_substr == 1) & (s_next == 0)) {
+            substrings = BoundedVec::new();
+        } else if (s == 6) & (s_next == 7) {
+            complete = true;
+        } else if (consecutive_substr == 1) {
+            substrings.push(current_substring);
+        }
+        s = s_next;
+        if complete == true {
+            break;
+        }
+    }
+    assert((s == 6) | (s == 7), f"no match: {s}");
+}
+"#;
+
+    fn dfa() -> Dfa {
+        Dfa::from_noir_code(CODE).unwrap()
+    }
+
+    #[test]
+    fn extracts_the_substring_of_a_match() {
+        let input = b"a=xyx;";
+        let run = dfa().run(input);
+        assert!(run.accepted);
+        assert_eq!(run.substrings, vec![2..5]);
+        assert_eq!(run.substrings_of(input), vec![b"xyx"]);
+    }
+
+    #[test]
+    fn rejects_an_incomplete_match() {
+        let run = dfa().run(b"a=xyx");
+        assert!(!run.accepted);
+    }
+
+    #[test]
+    fn discards_the_substring_interrupted_by_a_reset() {
+        let input = b"a=xy!\r\na=yy;";
+        let run = dfa().run(input);
+        assert!(run.accepted);
+        assert_eq!(run.substrings_of(input), vec![b"yy"]);
+    }
+
+    #[test]
+    fn stops_reading_after_the_match_is_complete() {
+        let input = b"a=xy;\r\na=yy";
+        let run = dfa().run(input);
+        assert!(run.accepted);
+        assert_eq!(run.substrings_of(input), vec![b"xy"]);
+        // The match is complete with the byte after the `;`
+        assert_eq!(run.steps.len(), 6);
+        assert_eq!(run.final_state, 7);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+};
+
+use serde::Serialize;
+
+use crate::{bytes::ByteString, db::DbEntry, dfa::Dfa, tester::TestResult};
+
+/// States, transitions and accepting states of the automaton exercised by a group of samples.
+#[derive(Serialize, Default)]
+pub struct SampleCoverage {
+    /// Number of samples in the group.
+    samples: usize,
+    /// States visited by the samples.
+    states: BTreeSet<u32>,
+    /// Transitions `(s, s_next)` taken by the samples.
+    transitions: BTreeSet<(u32, u32)>,
+    /// Accepting states in which the samples end.
+    accepting_states: BTreeSet<u32>,
+}
+
+impl SampleCoverage {
+    /// Runs the samples through the automaton and collects what they exercise.
+    fn new<'a>(dfa: &Dfa, samples: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let accepting_states = dfa.accepting_states();
+        let mut coverage = Self::default();
+        for sample in samples {
+            coverage.add(dfa, &accepting_states, sample);
+        }
+        coverage
+    }
+
+    /// Adds the states and transitions exercised by a sample.
+    fn add(&mut self, dfa: &Dfa, accepting_states: &BTreeSet<u32>, sample: &[u8]) {
+        let run = dfa.run(sample);
+        self.samples += 1;
+        self.states
+            .extend(run.steps.iter().flat_map(|&(s, s_next)| [s, s_next]));
+        self.transitions.extend(run.steps);
+        if accepting_states.contains(&run.final_state) {
+            self.accepting_states.insert(run.final_state);
+        }
+    }
+
+    /// Adds everything exercised by another group of samples.
+    fn extend(&mut self, other: &SampleCoverage) {
+        self.samples += other.samples;
+        self.states.extend(&other.states);
+        self.transitions.extend(&other.transitions);
+        self.accepting_states.extend(&other.accepting_states);
+    }
+}
+
+/// Coverage of the automaton of the generated code by the samples of an entry.
+#[derive(Serialize)]
+pub struct TransitionCoverage {
+    /// Number of states of the automaton.
+    total_states: usize,
+    /// Number of transitions `(s, s_next)` of the automaton.
+    total_transitions: usize,
+    /// Number of accepting states of the automaton.
+    total_accepting_states: usize,
+    /// Coverage of the given samples that should pass.
+    samples_pass: SampleCoverage,
+    /// Coverage of the given samples that should fail.
+    samples_fail: SampleCoverage,
+    /// Coverage of the random samples, the mutants and the inputs aimed at the uncovered
+    /// transitions.
+    random_samples: SampleCoverage,
+    /// Transitions that no sample takes.
+    uncovered_transitions: Vec<(u32, u32)>,
+    /// Accepting states in which no sample ends.
+    unreached_accepting_states: Vec<u32>,
+}
+
+impl TransitionCoverage {
+    /// Computes the coverage of the samples tested for an entry. The tested samples that are
+    /// not given in the entry are counted as random samples.
+    pub fn new(dfa: &Dfa, entry: &DbEntry, test_result: &TestResult) -> Self {
+        let pass_inputs = entry.pass_inputs();
+        let samples_pass =
+            SampleCoverage::new(dfa, pass_inputs.iter().map(|input| input.as_bytes()));
+        let samples_fail =
+            SampleCoverage::new(dfa, entry.samples_fail.iter().map(ByteString::as_bytes));
+        let random_samples = SampleCoverage::new(
+            dfa,
+            test_result
+                .successful_tests()
+                .iter()
+                .chain(
+                    test_result
+                        .failed_tests()
+                        .into_iter()
+                        .map(|(_, sample)| &sample.input),
+                )
+                .filter(|input| {
+                    !pass_inputs.contains(input) && !entry.samples_fail.contains(*input)
+                })
+                .map(|input| input.as_bytes()),
+        );
+
+        let mut all_samples = SampleCoverage::default();
+        for coverage in [&samples_pass, &samples_fail, &random_samples] {
+            all_samples.extend(coverage);
+        }
+        let accepting_states = dfa.accepting_states();
+        Self {
+            total_states: dfa.states().len(),
+            total_accepting_states: accepting_states.len(),
+            uncovered_transitions: dfa
+                .transitions()
+                .difference(&all_samples.transitions)
+                .copied()
+                .collect(),
+            total_transitions: dfa.transitions().len(),
+            unreached_accepting_states: accepting_states
+                .difference(&all_samples.accepting_states)
+                .copied()
+                .collect(),
+            samples_pass,
+            samples_fail,
+            random_samples,
+        }
+    }
+
+    /// Writes a line with the coverage of a group of samples.
+    fn fmt_group(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        coverage: &SampleCoverage,
+    ) -> fmt::Result {
+        writeln!(
+            f,
+            "  {:<15} {:>5} samples, {}/{} states, {}/{} transitions, {}/{} accepting states",
+            name,
+            coverage.samples,
+            coverage.states.len(),
+            self.total_states,
+            coverage.transitions.len(),
+            self.total_transitions,
+            coverage.accepting_states.len(),
+            self.total_accepting_states
+        )
+    }
+}
+
+impl Display for TransitionCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_group(f, "samples_pass", &self.samples_pass)?;
+        self.fmt_group(f, "samples_fail", &self.samples_fail)?;
+        self.fmt_group(f, "random samples", &self.random_samples)?;
+        if !self.uncovered_transitions.is_empty() {
+            writeln!(
+                f,
+                "  uncovered transitions: {:?}",
+                self.uncovered_transitions
+            )?;
+        }
+        if !self.unreached_accepting_states.is_empty() {
+            writeln!(
+                f,
+                "  unreached accepting states: {:?}",
+                self.unreached_accepting_states
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::db::DbEntry;
+
+// Selection of the database entries that are tested and benchmarked. An entry is selected if
+// it passes all the given filters, and a filter that is given several times accepts any of
+// its values. This is not a doc comment, since clap would use it as the description of the
+// program in `--help` when the arguments are flattened into the command line.
+#[derive(clap::Args, Debug)]
+pub struct EntryFilter {
+    /// Only process the entries with this `circuit_name` or `test_name`
+    #[arg(long)]
+    only: Vec<String>,
+    /// Only process the entries with this tag
+    #[arg(long)]
+    tag: Vec<String>,
+    /// Only process the entries in these positions of the database, given as `3`, `3..7`,
+    /// `3..=7` or `3..`
+    #[arg(long, value_parser = parse_index_range)]
+    index: Vec<Range<usize>>,
+    /// Only process the entries whose complete regex matches this pattern
+    #[arg(long = "match", value_parser = Regex::new)]
+    pattern: Option<Regex>,
+}
+
+impl EntryFilter {
+    /// Whether the entry in the given position of the database is selected.
+    pub fn matches(&self, index: usize, entry: &DbEntry) -> bool {
+        let name_matches = self.only.is_empty()
+            || [&entry.circuit_name, &entry.test_name]
+                .into_iter()
+                .flatten()
+                .any(|name| self.only.contains(name));
+        let tag_matches =
+            self.tag.is_empty() || entry.tags.iter().any(|tag| self.tag.contains(tag));
+        let index_matches =
+            self.index.is_empty() || self.index.iter().any(|range| range.contains(&index));
+        let pattern_matches = self
+            .pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&entry.regex.complete_regex()));
+        name_matches && tag_matches && index_matches && pattern_matches
+    }
+}
+
+/// Parses a position (`3`) or a range of positions (`3..7`, `3..=7`, `..7` or `3..`) of the
+/// database.
+fn parse_index_range(value: &str) -> Result<Range<usize>, String> {
+    let parse_bound = |bound: &str, default: usize| {
+        if bound.is_empty() {
+            Ok(default)
+        } else {
+            bound
+                .parse::<usize>()
+                .map_err(|err| format!("invalid index {:?}: {}", bound, err))
+        }
+    };
+    // The ranges are half-open, so the last position can't be included
+    let after = |index: usize| {
+        index
+            .checked_add(1)
+            .ok_or_else(|| format!("the index {} is too large", index))
+    };
+    match value.split_once("..") {
+        None if value.is_empty() => Err("the index is empty".to_string()),
+        None => {
+            let index = parse_bound(value, 0)?;
+            Ok(index..after(index)?)
+        }
+        Some((start, end)) => {
+            let start = parse_bound(start, 0)?;
+            let end = match end.strip_prefix('=') {
+                Some(end) => after(parse_bound(end, usize::MAX - 1)?)?,
+                None => parse_bound(end, usize::MAX)?,
+            };
+            Ok(start..end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_index_ranges() {
+        assert_eq!(parse_index_range("3"), Ok(3..4));
+        assert_eq!(parse_index_range("3..7"), Ok(3..7));
+        assert_eq!(parse_index_range("3..=7"), Ok(3..8));
+        assert_eq!(parse_index_range("..7"), Ok(0..7));
+        assert_eq!(parse_index_range("3.."), Ok(3..usize::MAX));
+        assert_eq!(parse_index_range("..="), Ok(0..usize::MAX));
+    }
+
+    #[test]
+    fn rejects_the_invalid_index_ranges() {
+        assert!(parse_index_range("").is_err());
+        assert!(parse_index_range("a..3").is_err());
+        assert!(parse_index_range("3...7").is_err());
+        assert!(parse_index_range("-1").is_err());
+        assert!(parse_index_range(&usize::MAX.to_string()).is_err());
+        assert!(parse_index_range(&format!("0..={}", usize::MAX)).is_err());
+    }
+}
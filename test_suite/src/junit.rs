@@ -0,0 +1,274 @@
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::Context;
+
+use crate::report::{EntryStatus, RunSummary};
+
+/// Writes the summary of the run as a JUnit XML report. Each database entry is a test suite
+/// and each of its samples is a test case. Samples that were judged incorrectly are reported
+/// as failures, and errors that prevented testing the entry are reported as an error test case.
+/// Expected failures are reported as skipped test cases, and a skipped entry as a single
+/// skipped test case. Unexpected passes are passed test cases marked in their output, with a
+/// single test case for an entry expected to fail as a whole.
+pub fn save_junit_report(summary: &RunSummary, path: &Path) -> anyhow::Result<()> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+
+    for entry in summary.entries() {
+        // The entries processed at several input sizes have a test suite for each size
+        let swept = summary
+            .entries()
+            .iter()
+            .filter(|other| other.index == entry.index)
+            .count()
+            > 1;
+        let suite_name = if swept {
+            escape(&format!(
+                "{} size {}",
+                entry.display_name(),
+                entry.input_size
+            ))
+        } else {
+            escape(&entry.display_name())
+        };
+        let successful_tests = entry
+            .test_result
+            .as_ref()
+            .map(|test_result| test_result.successful_tests())
+            .unwrap_or_default();
+        let failed_tests = entry
+            .test_result
+            .as_ref()
+            .map(|test_result| test_result.failed_tests())
+            .unwrap_or_default();
+        let skipped_tests = entry
+            .test_result
+            .as_ref()
+            .map(|test_result| test_result.skipped_tests())
+            .unwrap_or_default();
+        let expected_failures = entry
+            .test_result
+            .as_ref()
+            .map(|test_result| test_result.expected_failures())
+            .unwrap_or_default();
+        let unexpected_passes = entry
+            .test_result
+            .as_ref()
+            .map(|test_result| test_result.unexpected_passes())
+            .unwrap_or_default();
+        let errors = usize::from(!entry.errors.is_empty());
+        let status = entry.status();
+        // With an expected failure for the whole entry, all the failures are expected
+        let (failures, expected_failure_count) = if status == EntryStatus::ExpectedFailure {
+            (0, failed_tests.len() + expected_failures.len())
+        } else {
+            (failed_tests.len(), expected_failures.len())
+        };
+        let skipped_entry = usize::from(status == EntryStatus::Skipped);
+        // An entry expected to fail as a whole that passed has no sample to report it on
+        let unexpected_entry_pass =
+            usize::from(status == EntryStatus::UnexpectedPass && unexpected_passes.is_empty());
+
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\">",
+            suite_name,
+            successful_tests.len()
+                + failed_tests.len()
+                + skipped_tests.len()
+                + expected_failures.len()
+                + unexpected_passes.len()
+                + errors
+                + skipped_entry
+                + unexpected_entry_pass,
+            failures,
+            errors,
+            skipped_tests.len() + expected_failure_count + skipped_entry
+        )?;
+        writeln!(
+            xml,
+            "    <properties>\n      <property name=\"regex\" value=\"{}\"/>",
+            escape(&entry.regex)
+        )?;
+        writeln!(
+            xml,
+            "      <property name=\"input_size\" value=\"{}\"/>",
+            entry.input_size
+        )?;
+        writeln!(
+            xml,
+            "      <property name=\"source\" value=\"{}\"/>",
+            escape(&entry.source)
+        )?;
+        if let Some(seed) = entry.seed {
+            writeln!(xml, "      <property name=\"seed\" value=\"{}\"/>", seed)?;
+        }
+        writeln!(xml, "    </properties>")?;
+
+        for sample in successful_tests {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\"/>",
+                suite_name,
+                escape(&format!("{:?}", sample))
+            )?;
+        }
+
+        for sample in unexpected_passes {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\">\n      <system-out>unexpected pass: the sample is in the expected_failure of the entry</system-out>\n    </testcase>",
+                suite_name,
+                escape(&format!("{:?}", sample))
+            )?;
+        }
+
+        let expected_failure_reason = entry
+            .expected_failure
+            .as_ref()
+            .map(|expected_failure| expected_failure.reason.as_str())
+            .unwrap_or_default();
+        if unexpected_entry_pass > 0 {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"expected_failure\">\n      <system-out>unexpected pass: the entry passed, but it is expected to fail: {}</system-out>\n    </testcase>",
+                suite_name,
+                escape(expected_failure_reason)
+            )?;
+        }
+
+        for sample in expected_failures.iter().chain(
+            failed_tests
+                .iter()
+                .filter(|_| status == EntryStatus::ExpectedFailure)
+                .map(|(_, sample)| *sample),
+        ) {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\">\n      <skipped message=\"expected failure: {}\">{}</skipped>\n    </testcase>",
+                suite_name,
+                escape(&format!("{:?}", sample.input)),
+                escape(expected_failure_reason),
+                sample.cause
+            )?;
+        }
+
+        for (category, sample) in failed_tests
+            .iter()
+            .filter(|_| status != EntryStatus::ExpectedFailure)
+        {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\">",
+                suite_name,
+                escape(&format!("{:?}", sample.input))
+            )?;
+            let minimal_input = sample
+                .minimal_input
+                .as_ref()
+                .map(|input| escape(&format!("\nminimal input: {:?}", input)))
+                .unwrap_or_default();
+            writeln!(
+                xml,
+                "      <failure message=\"{}: {}\" type=\"{:?}\">sample bytes: {:?}{}\n\nnargo output:\n{}</failure>",
+                category,
+                sample.cause,
+                sample.cause,
+                sample.input.as_bytes(),
+                minimal_input,
+                escape(&sample.nargo_output)
+            )?;
+            writeln!(xml, "    </testcase>")?;
+        }
+
+        for sample in skipped_tests {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                suite_name,
+                escape(&format!("{:?}", sample.input)),
+                escape(&sample.reason)
+            )?;
+        }
+
+        if let Some(skip) = &entry.skip {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"entry\">\n      <skipped message=\"{}\"/>\n    </testcase>",
+                suite_name,
+                escape(&skip.reason)
+            )?;
+        }
+
+        if !entry.errors.is_empty() {
+            writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"setup\">\n      <error message=\"{}\">{}</error>\n    </testcase>",
+                suite_name,
+                escape(entry.errors[0].lines().next().unwrap_or_default()),
+                escape(&entry.errors.join("\n"))
+            )?;
+        }
+
+        writeln!(xml, "  </testsuite>")?;
+    }
+    writeln!(xml, "</testsuites>")?;
+
+    fs::write(path, xml).context(format!(
+        "error writing the JUnit report to the path {:?}",
+        path
+    ))?;
+    Ok(())
+}
+
+/// Escapes a string to be used as XML text or attribute value. Control characters that are
+/// not allowed in XML (e.g. the color codes in the nargo output) are written in the Rust
+/// escaped form.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\t' | '\r' => escaped.push(c),
+            c if c.is_control() => escaped.extend(c.escape_default()),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::ExpectedFailure,
+        report::EntryReport,
+        tester::{StandardTestResult, TestResult},
+    };
+
+    #[test]
+    fn marks_the_unexpected_pass_of_an_entry() {
+        let entry = EntryReport {
+            regex: "a+".to_string(),
+            input_size: 16,
+            test_result: Some(TestResult::Standard(StandardTestResult::default())),
+            expected_failure: Some(ExpectedFailure {
+                reason: "known bug".to_string(),
+                samples: Vec::new(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(entry.status(), EntryStatus::UnexpectedPass);
+        let path = std::env::temp_dir().join(format!("junit-xpass-{}.xml", std::process::id()));
+        save_junit_report(&RunSummary::new(vec![entry]), &path).unwrap();
+        let xml = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(xml.contains("tests=\"1\" failures=\"0\""));
+        assert!(xml.contains(
+            "<system-out>unexpected pass: the entry passed, but it is expected to fail: known bug</system-out>"
+        ));
+    }
+}
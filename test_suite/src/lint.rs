@@ -0,0 +1,305 @@
+use std::fmt::{self, Display};
+
+use crate::{
+    bytes::ByteString,
+    db::{DbEntry, RawRegex, RegexInput, SamplesPass},
+    oracle::Oracle,
+};
+
+/// Problem found in the database, with the location of the value that causes it.
+pub struct LintIssue {
+    /// Database file and JSON pointer of the value in the file, e.g.
+    /// `regex_db.json#/database/3/samples_pass/1`.
+    pub location: String,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Checks the entries of the database, given with their positions, against the oracle and the
+/// structural rules of the database:
+/// - the regex must be valid.
+/// - the passing samples must match the regex and the failing samples must not, according to
+///   the match mode of the entry. A sample can't be both passing and failing.
+/// - the samples can't be longer than the input size.
+/// - the benchmarking string must be as long as the input size, or not longer than the smallest
+///   size of `input_sizes`, since it is padded up to each size. With `with_time`, the entries
+///   that are benchmarked, because of `with_bench` or `bench_all`, must have one, as the
+///   proving time benchmark runs the circuit on it.
+/// - the passing samples have expected substrings if and only if `gen_substrs` is set, and
+///   they can't be empty when the regex extracts a non-empty substring from the sample.
+/// - the samples of the expected failure must be samples of the entry.
+/// - two entries can't have the same regex, `gen_substrs` and input size.
+pub fn lint_entries(
+    entries: &[(usize, DbEntry)],
+    bench_all: bool,
+    with_time: bool,
+) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    for (position, (_, entry)) in entries.iter().enumerate() {
+        lint_entry(entry, bench_all, with_time, &mut issues);
+
+        if let Some((other_index, other)) = entries[..position].iter().find(|(_, other)| {
+            other.regex.complete_regex() == entry.regex.complete_regex()
+                && other.gen_substrs == entry.gen_substrs
+                && other.sizes(&[]) == entry.sizes(&[])
+        }) {
+            issues.push(LintIssue {
+                location: entry.source.to_string(),
+                message: format!("duplicate of the entry {} at {}", other_index, other.source),
+            });
+        }
+    }
+    issues
+}
+
+/// Checks a single entry of the database.
+fn lint_entry(entry: &DbEntry, bench_all: bool, with_time: bool, issues: &mut Vec<LintIssue>) {
+    let mut issue = |location: String, message: String| {
+        issues.push(LintIssue {
+            location: format!("{}{}", entry.source, location),
+            message,
+        })
+    };
+
+    let complete_regex = entry.regex.complete_regex();
+    let oracle = match Oracle::new(&complete_regex, entry.match_mode) {
+        Ok(oracle) => Some(oracle),
+        Err(err) => {
+            issue("/regex".to_string(), format!("invalid regex: {:#}", err));
+            None
+        }
+    };
+
+    let pass_samples: Vec<(String, &ByteString)> = match &entry.samples_pass {
+        SamplesPass::WithSubstrs(samples) => samples
+            .iter()
+            .enumerate()
+            .map(|(j, sample)| (format!("/samples_pass/{}/input", j), &sample.input))
+            .collect(),
+        SamplesPass::WithoutSubstrs(samples) => samples
+            .iter()
+            .enumerate()
+            .map(|(j, sample)| (format!("/samples_pass/{}", j), sample))
+            .collect(),
+    };
+    let fail_samples: Vec<(String, &ByteString)> = entry
+        .samples_fail
+        .iter()
+        .enumerate()
+        .map(|(j, sample)| (format!("/samples_fail/{}", j), sample))
+        .collect();
+
+    let max_len = entry
+        .sizes(&[])
+        .into_iter()
+        .min()
+        .unwrap_or(entry.input_size);
+    for (should_match, samples) in [(true, &pass_samples), (false, &fail_samples)] {
+        for (location, sample) in samples {
+            if sample.len() > max_len {
+                issue(
+                    location.clone(),
+                    format!(
+                        "the sample is {} bytes long, more than the input size {}",
+                        sample.len(),
+                        max_len
+                    ),
+                );
+            }
+            if let Some(oracle) = &oracle {
+                if oracle.accepts(sample) != should_match {
+                    issue(
+                        location.clone(),
+                        format!(
+                            "the sample {:?} {} the regex with the match mode {:?}",
+                            sample,
+                            if should_match {
+                                "doesn't match"
+                            } else {
+                                "matches"
+                            },
+                            entry.match_mode
+                        ),
+                    );
+                }
+            }
+        }
+    }
+    for (location, sample) in &fail_samples {
+        if let Some((pass_location, _)) = pass_samples.iter().find(|(_, pass)| pass == sample) {
+            issue(
+                location.clone(),
+                format!(
+                    "the sample is also a passing sample at {}{}",
+                    entry.source, pass_location
+                ),
+            );
+        }
+    }
+
+    if let Some(expected_failure) = &entry.expected_failure {
+        for (j, sample) in expected_failure.samples.iter().enumerate() {
+            if !pass_samples.iter().any(|(_, pass)| *pass == sample)
+                && !entry.samples_fail.contains(sample)
+            {
+                issue(
+                    format!("/expected_failure/samples/{}", j),
+                    format!("the sample {:?} is not a sample of the entry", sample),
+                );
+            }
+        }
+    }
+
+    let timed = with_time && (entry.with_bench || bench_all) && entry.skip.is_none();
+    if entry.benchmark_str.is_empty() {
+        if timed {
+            issue(
+                "/benchmark_str".to_string(),
+                format!(
+                    "the entry is benchmarked with time ({}), but it has no benchmarking string",
+                    if entry.with_bench {
+                        "with_bench"
+                    } else {
+                        "bench_all"
+                    }
+                ),
+            );
+        }
+    } else if entry.input_sizes.is_empty() && entry.benchmark_str.len() != entry.input_size {
+        issue(
+            "/benchmark_str".to_string(),
+            format!(
+                "the benchmarking string is {} bytes long, but the input size is {}",
+                entry.benchmark_str.len(),
+                entry.input_size
+            ),
+        );
+    } else if entry.benchmark_str.len() > max_len {
+        issue(
+            "/benchmark_str".to_string(),
+            format!(
+                "the benchmarking string is {} bytes long, more than the input size {}",
+                entry.benchmark_str.len(),
+                max_len
+            ),
+        );
+    }
+
+    match &entry.samples_pass {
+        SamplesPass::WithSubstrs(samples) => {
+            if !entry.gen_substrs {
+                issue(
+                    "/samples_pass".to_string(),
+                    "the samples have expected substrings, but gen_substrs is not set".to_string(),
+                );
+            }
+            for (j, sample) in samples.iter().enumerate() {
+                if sample.expected_substrings.is_empty() && has_substrings(entry, &sample.input) {
+                    issue(
+                        format!("/samples_pass/{}/expected_substrings", j),
+                        "no expected substrings, but the regex extracts a non-empty substring \
+                         from the sample"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        SamplesPass::WithoutSubstrs(samples) => {
+            if entry.gen_substrs && !samples.is_empty() {
+                issue(
+                    "/samples_pass".to_string(),
+                    "gen_substrs is set, but the samples have no expected substrings".to_string(),
+                );
+            }
+        }
+    }
+}
+
+/// Whether the regex of the entry extracts a non-empty substring from the sample: a public
+/// fragment of a decomposed regex that can't be empty, or a capture group of a raw regex that
+/// matches a non-empty part of the sample. The substrings of a raw regex with explicit
+/// transitions are unknown.
+fn has_substrings(entry: &DbEntry, sample: &[u8]) -> bool {
+    match &entry.regex {
+        RegexInput::Decomposed(parts) => parts.iter().any(|part| {
+            part.is_public
+                && regex::Regex::new(&format!("^(?:{})$", part.regex_def))
+                    .is_ok_and(|fragment| !fragment.is_match(""))
+        }),
+        RegexInput::Raw(RawRegex::Simple(regex)) => regex::bytes::Regex::new(regex)
+            .ok()
+            .and_then(|regex| regex.captures(sample))
+            .is_some_and(|captures| {
+                captures
+                    .iter()
+                    .skip(1)
+                    .flatten()
+                    .any(|capture| !capture.is_empty())
+            }),
+        RegexInput::Raw(RawRegex::WithTransitions { .. }) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(fields: serde_json::Value) -> (usize, DbEntry) {
+        let mut entry = serde_json::json!({
+            "regex": {"raw": "ab"},
+            "input_size": 4,
+            "samples_pass": ["ab"],
+            "samples_fail": ["ba"],
+        });
+        entry
+            .as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        (0, serde_json::from_value(entry).unwrap())
+    }
+
+    fn messages(entries: &[(usize, DbEntry)], bench_all: bool, with_time: bool) -> Vec<String> {
+        lint_entries(entries, bench_all, with_time)
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn requires_a_benchmarking_string_for_the_entries_benchmarked_with_time() {
+        let without_bench = [entry(serde_json::json!({}))];
+        assert!(messages(&without_bench, false, true).is_empty());
+        assert_eq!(messages(&without_bench, true, true).len(), 1);
+        // The benchmark without time doesn't use the benchmarking string
+        assert!(messages(&without_bench, true, false).is_empty());
+
+        let with_bench = [entry(serde_json::json!({"with_bench": true}))];
+        assert_eq!(messages(&with_bench, false, true).len(), 1);
+        assert!(messages(&with_bench, false, false).is_empty());
+
+        let skipped = [entry(serde_json::json!({"skip": {"reason": "slow"}}))];
+        assert!(messages(&skipped, true, true).is_empty());
+    }
+
+    #[test]
+    fn checks_the_length_of_the_benchmarking_string() {
+        let exact = [entry(serde_json::json!({"benchmark_str": "abab"}))];
+        assert!(messages(&exact, true, true).is_empty());
+
+        let short = [entry(serde_json::json!({"benchmark_str": "ab"}))];
+        assert_eq!(messages(&short, true, true).len(), 1);
+
+        // The benchmarking string is padded up to each size of the sweep
+        let swept = [entry(serde_json::json!({
+            "benchmark_str": "ab",
+            "input_sizes": [4, 8],
+        }))];
+        assert!(messages(&swept, true, true).is_empty());
+    }
+}
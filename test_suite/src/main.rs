@@ -1,17 +1,49 @@
 mod bench;
+mod bytes;
+mod circom_import;
 mod code;
 mod compiler;
 mod constants;
+mod coverage;
 mod db;
+mod dfa;
+mod dfa_coverage;
+mod filter;
+mod junit;
+mod lint;
+mod mutation;
+mod oracle;
+mod padding;
+mod record;
+mod report;
+mod shrink;
 mod tester;
+mod transitions;
+mod workspace;
 
+use anyhow::Context;
 use bench::{benchmark_noir_code, BenchReport};
 use clap::{Parser, Subcommand};
 use code::Code;
-use db::RegexDb;
-use log::{self, error, info};
-use std::{error::Error, path::Path};
-use tester::test_regex;
+use coverage::CoverageReport;
+use db::{DbEntry, RawRegex, RegexDb, RegexInput};
+use dfa::Dfa;
+use dfa_coverage::TransitionCoverage;
+use filter::EntryFilter;
+use log::{self, error, info, warn};
+use record::FailurePatch;
+use report::{EntryReport, EntryStatus, RunSummary};
+use std::{
+    collections::{btree_map, BTreeMap},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::Duration,
+};
+use tester::{test_padding, test_regex, TestOptions};
+use workspace::Workspace;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -22,13 +54,126 @@ struct Args {
     /// If you want to run the testing
     #[arg(long, short)]
     test: bool,
-    /// If you want to run the benchmarking
+    /// Number of entries processed in parallel. Each job works on its own temporary
+    /// copy of the execution project.
+    #[arg(long, short, default_value_t = 1)]
+    jobs: usize,
+    /// Path of the JSON file where the summary of the run is written
+    #[arg(long, default_value_t = String::from(constants::DEFAULT_SUMMARY_FILE))]
+    summary: String,
+    /// Optional path of a JUnit XML file where the test results are written
+    #[arg(long)]
+    junit: Option<String>,
+    /// Seed of the random samples of the entries that don't have their own `seed`. By
+    /// default, a new seed is drawn for each entry
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Shrink the failing samples to minimal counterexamples. Each step of the shrinking runs
+    /// `nargo test` again
+    #[arg(long)]
+    shrink: bool,
+    /// Also test the given samples padded with zeros up to the input size, ending at the last
+    /// byte of the input and followed by garbage, and report the changes of outcome
+    #[arg(long)]
+    padding: bool,
+    /// Test and benchmark each entry at these input sizes, overriding the `input_sizes` of the
+    /// entries, e.g. `--sizes 64,256,1024`
+    #[arg(long, value_delimiter = ',')]
+    sizes: Vec<usize>,
+    /// Number of random samples tested for each entry, overriding the `random_samples` of the
+    /// entries
+    #[arg(long)]
+    random_samples: Option<usize>,
+    /// Keep generating and testing random samples for each entry until this number of seconds
+    /// has passed
+    #[arg(long)]
+    random_budget_secs: Option<u64>,
+    /// Write the random samples, mutants and inputs of uncovered transitions that were judged
+    /// incorrectly to this file, as samples that can be added to the database with
+    /// `merge-failures`
+    #[arg(long)]
+    record_failures: Option<String>,
+    #[command(flatten)]
+    filter: EntryFilter,
+    /// If you want to run the benchmarking, or another tool
     #[clap(subcommand)]
-    bench: Option<BenchExecType>,
+    command: Option<Command>,
 }
 
-/// Type of the benchmark that you want to execute.
 #[derive(Debug, Subcommand)]
+enum Command {
+    /// The benchmark will include time execution.
+    WithTime,
+    /// The benchmark will not include time execution.
+    NoTime,
+    /// Derives the substring transitions of a raw regex from its capture groups and prints
+    /// the regex in the format of the database.
+    DeriveTransitions {
+        /// Raw regex in which each capture group marks a substring
+        regex: String,
+    },
+    /// Reports which circom test cases are implemented by the database entries, which ones
+    /// by the hardcoded tests and which ones are missing.
+    CircomCoverage {
+        /// Files with the names of the circom test cases: circom `.test.js` files or text
+        /// files with one name per line
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Path of the folder with the hardcoded tests
+        #[arg(long, default_value_t = String::from(constants::DEFAULT_HARDCODED_TESTS_PATH))]
+        hardcoded: String,
+    },
+    /// Imports the test cases of circom `.test.js` files of zk-regex as database entries and
+    /// prints them, or merges them into the database.
+    ImportCircom {
+        /// Circom `.test.js` files. The JSON definitions of their circuits are read relative to
+        /// each file
+        #[arg(required = true)]
+        files: Vec<String>,
+        /// Merge the imported entries into the database file instead of printing them
+        #[arg(long)]
+        merge: bool,
+    },
+    /// Adds the samples recorded with `--record-failures` to their entries in the database.
+    MergeFailures {
+        /// File written by `--record-failures`
+        patch: String,
+    },
+    /// Checks the selected entries of the database against the Rust regex lib and the rules of
+    /// the database, and prints the location of each problem.
+    Lint {
+        /// Also require a benchmarking string in the benchmarked entries, which the `with-time`
+        /// benchmark runs the circuit on
+        #[arg(long)]
+        with_time: bool,
+    },
+    /// Prints the JSON Schema of the database files, which editors can use to validate and
+    /// autocomplete the database.
+    Schema {
+        /// Write the schema to this file instead of printing it
+        #[arg(long)]
+        output: Option<String>,
+    },
+}
+
+impl Command {
+    /// Returns the type of the benchmark requested by the command, if any.
+    fn bench_exec_type(&self) -> Option<BenchExecType> {
+        match self {
+            Command::WithTime => Some(BenchExecType::WithTime),
+            Command::NoTime => Some(BenchExecType::NoTime),
+            Command::DeriveTransitions { .. }
+            | Command::CircomCoverage { .. }
+            | Command::ImportCircom { .. }
+            | Command::MergeFailures { .. }
+            | Command::Lint { .. }
+            | Command::Schema { .. } => None,
+        }
+    }
+}
+
+/// Type of the benchmark that you want to execute.
+#[derive(Debug)]
 enum BenchExecType {
     /// The benchmark will include time execution.
     WithTime,
@@ -36,101 +181,551 @@ enum BenchExecType {
     NoTime,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> ExitCode {
     env_logger::init();
     // Parse command-line arguments
     let args = Args::parse();
+    let result = match &args.command {
+        Some(Command::DeriveTransitions { regex }) => {
+            print_derived_transitions(regex).map(|_| ExitCode::SUCCESS)
+        }
+        Some(Command::CircomCoverage { files, hardcoded }) => {
+            print_circom_coverage(&args, files, hardcoded)
+        }
+        Some(Command::ImportCircom { files, merge }) => {
+            import_circom(&args, files, *merge).map(|_| ExitCode::SUCCESS)
+        }
+        Some(Command::MergeFailures { patch }) => {
+            merge_failures(&args, patch).map(|_| ExitCode::SUCCESS)
+        }
+        Some(Command::Lint { with_time }) => print_lint_issues(&args, *with_time),
+        Some(Command::Schema { output }) => {
+            print_schema(output.as_deref()).map(|_| ExitCode::SUCCESS)
+        }
+        _ => run(&args).map(|summary| summary.exit_code()),
+    };
+    match result {
+        Ok(exit_code) => exit_code,
+        Err(err) => {
+            error!("error running the test suite: {:?}", err);
+            ExitCode::from(constants::EXIT_INFRASTRUCTURE_ERROR)
+        }
+    }
+}
+
+/// Runs the testing and benchmarking over the database and returns the summary of the run.
+fn run(args: &Args) -> anyhow::Result<RunSummary> {
     info!("starting regex tests");
     // Reads the database from the given path or use the default one
     let database_path = Path::new(&args.db);
-    let database = RegexDb::load_from_file(database_path).map_err(|err| {
-        error!("error creating the database: {}", err);
-        err
-    })?;
+    let database = RegexDb::load_from_file(database_path).context("error creating the database")?;
 
     let benchmark_all = database.bench_all;
+    // An entry is processed once for each of its input sizes
+    let entries: Vec<(usize, DbEntry)> = database
+        .into_iter()
+        .enumerate()
+        .filter(|(index, regex_input)| args.filter.matches(*index, regex_input))
+        .flat_map(|(index, regex_input)| {
+            regex_input
+                .sweep(&args.sizes)
+                .into_iter()
+                .map(move |sized_entry| (index, sized_entry))
+        })
+        .collect();
+    if entries.is_empty() {
+        warn!("no entry of the database is selected by the filters");
+    }
+    let mut entry_reports = if args.jobs > 1 {
+        info!("running with {} parallel jobs", args.jobs);
+        process_entries_in_parallel(&entries, args, benchmark_all)?
+    } else {
+        let workspace = Workspace::default();
+        entries
+            .iter()
+            .map(|(index, regex_input)| {
+                process_entry(*index, regex_input, args, benchmark_all, &workspace)
+            })
+            .collect()
+    };
+
     let mut bench_report = BenchReport::default();
-    for regex_input in database {
-        let mut code_read_result = Code::new(&regex_input);
-        match &mut code_read_result {
-            Ok(code) => {
-                // info!("compiling regex {}", regex_input.regex.complete_regex());
-                // let _ = code.write_to_path(Path::new(constants::DEFAULT_PROJECT_MAIN_FILE));
-                // let compilation_result = compiler::compile_noir_project();
-                // match compilation_result {
-                //     Ok(_) => info!(
-                //         "compilation success for regex {}",
-                //         regex_input.regex.complete_regex()
-                //     ),
-                //     Err(e) => {
-                //         error!(
-                //             "error compiling the noir project for regex {}: {:?}",
-                //             regex_input.regex.complete_regex(),
-                //             e
-                //         );
-                //         continue;
-                //     }
-                // }
-                if args.test {
-                    info!("testing regex {}", regex_input.regex.complete_regex());
-                    match test_regex(&regex_input, code) {
-                        Ok(test_result) => {
-                            info!(
-                                "test passed correctly for regex {}:\n{}",
+    for bench_result in entry_reports
+        .iter_mut()
+        .filter_map(|entry_report| entry_report.bench_result.take())
+    {
+        bench_report.push_result(bench_result);
+    }
+
+    // Save the bench results.
+    if !bench_report.is_empty() {
+        info!("saving benchmark results into CSV");
+        bench_report.save(Path::new(constants::DEFAULT_BENCH_RESULT_FILE))?;
+    }
+
+    // Print and save the summary of the run.
+    let summary = RunSummary::new(entry_reports);
+    println!("{}", summary);
+    summary.save(Path::new(&args.summary))?;
+    if let Some(junit_path) = &args.junit {
+        info!("saving JUnit report into {}", junit_path);
+        junit::save_junit_report(&summary, Path::new(junit_path))?;
+    }
+    if let Some(record_path) = &args.record_failures {
+        let patch = FailurePatch::new(&entries, summary.entries());
+        info!(
+            "recording {} failures into {}",
+            patch.failures.len(),
+            record_path
+        );
+        patch.save(Path::new(record_path))?;
+    }
+
+    Ok(summary)
+}
+
+/// Derives the substring transitions of a raw regex from its capture groups and prints the
+/// regex with the transitions, ready to be used in an entry of the database.
+fn print_derived_transitions(regex: &str) -> anyhow::Result<()> {
+    let transitions = transitions::derive_transitions(regex, &Workspace::default())
+        .context("error deriving the substring transitions from the capture groups")?;
+    let regex_input = RegexInput::Raw(RawRegex::WithTransitions {
+        regex: transitions::plain_regex(regex),
+        transitions: Some(transitions),
+    });
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&regex_input).context("error serializing the regex")?
+    );
+    Ok(())
+}
+
+/// Imports the entries of the circom test files and prints them as a JSON array, or merges
+/// them into the database file.
+fn import_circom(args: &Args, files: &[String], merge: bool) -> anyhow::Result<()> {
+    let mut imported = Vec::new();
+    for file in files {
+        let entries = circom_import::import_test_file(Path::new(file))?;
+        circom_import::merge_entries(&mut imported, entries);
+    }
+    if !merge {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&imported).context("error serializing the entries")?
+        );
+        return Ok(());
+    }
+
+    let root_file = Path::new(&args.db);
+    let original: Vec<DbEntry> = RegexDb::load_from_file(root_file)?.into_iter().collect();
+    let mut database = original.clone();
+    let stats = circom_import::merge_entries(&mut database, imported);
+    if stats.entries == 0 && stats.samples == 0 {
+        info!("the database already has all the imported test cases");
+        return Ok(());
+    }
+    write_changed_entries(root_file, &original, &database)?;
+    info!(
+        "{} entries and {} samples of existing entries were imported into {}",
+        stats.entries, stats.samples, args.db
+    );
+    Ok(())
+}
+
+/// Merges the failures recorded with `--record-failures` into the database file.
+fn merge_failures(args: &Args, patch_path: &str) -> anyhow::Result<()> {
+    let patch = FailurePatch::load(Path::new(patch_path))?;
+    let root_file = Path::new(&args.db);
+    let original: Vec<DbEntry> = RegexDb::load_from_file(root_file)?.into_iter().collect();
+    let mut database = original.clone();
+    let added = patch.merge_into(&mut database);
+    if added == 0 {
+        info!("the database already has all the recorded failures");
+        return Ok(());
+    }
+    write_changed_entries(root_file, &original, &database)?;
+    info!(
+        "{} recorded failures were added as samples into {}",
+        added, args.db
+    );
+    Ok(())
+}
+
+/// Writes the entries of the database that differ from the original ones. Only the changed
+/// entries are rewritten, each one in its own file, so the other entries keep their fields as
+/// written in the files. The new entries are appended to the given root database file.
+fn write_changed_entries(
+    root_file: &Path,
+    original: &[DbEntry],
+    database: &[DbEntry],
+) -> anyhow::Result<()> {
+    let mut files: BTreeMap<PathBuf, serde_json::Value> = BTreeMap::new();
+    for (position, entry) in database.iter().enumerate() {
+        let value = serde_json::to_value(entry).context("error serializing the entries")?;
+        let (file, index) = match original.get(position) {
+            Some(original_entry) if serde_json::to_value(original_entry)? == value => continue,
+            Some(_) => (entry.source.file.clone(), Some(entry.source.index)),
+            None => (root_file.to_path_buf(), None),
+        };
+        let db = match files.entry(file) {
+            btree_map::Entry::Occupied(db) => db.into_mut(),
+            btree_map::Entry::Vacant(db) => {
+                let json_db = read_json_database(db.key())?;
+                db.insert(json_db)
+            }
+        };
+        let db_entries = db
+            .as_object_mut()
+            .context("the database is not an object")?
+            .entry("database")
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()))
+            .as_array_mut()
+            .context("the entries of the database are not an array")?;
+        match index {
+            Some(index) => db_entries[index] = value,
+            None => db_entries.push(value),
+        }
+    }
+    for (file, db) in files {
+        fs::write(
+            &file,
+            serde_json::to_string_pretty(&db).context("error serializing the database")? + "\n",
+        )
+        .context(format!("error writing the database {:?}", file))?;
+    }
+    Ok(())
+}
+
+/// Reads a database file that is going to be rewritten, which must be a JSON file.
+fn read_json_database(file: &Path) -> anyhow::Result<serde_json::Value> {
+    if file
+        .extension()
+        .is_some_and(|extension| extension != "json")
+    {
+        anyhow::bail!(
+            "only JSON database files can be rewritten, but the entries to write are in {:?}",
+            file
+        );
+    }
+    let contents =
+        fs::read_to_string(file).context(format!("error reading the database {:?}", file))?;
+    serde_json::from_str(&contents).context(format!("error parsing the database {:?}", file))
+}
+
+/// Prints the JSON Schema of the database or writes it to the given file.
+fn print_schema(output: Option<&str>) -> anyhow::Result<()> {
+    let schema =
+        serde_json::to_string_pretty(&RegexDb::schema()).context("error serializing the schema")?;
+    match output {
+        Some(path) => fs::write(path, schema + "\n")
+            .context(format!("error writing the schema to the path {:?}", path))?,
+        None => println!("{}", schema),
+    }
+    Ok(())
+}
+
+/// Lints the selected entries of the database and prints the problems. Returns a failure exit
+/// code if there is any problem. With `with_time`, the benchmarked entries must have a
+/// benchmarking string.
+fn print_lint_issues(args: &Args, with_time: bool) -> anyhow::Result<ExitCode> {
+    let database =
+        RegexDb::load_from_file(Path::new(&args.db)).context("error creating the database")?;
+    let bench_all = database.bench_all;
+    let entries: Vec<(usize, DbEntry)> = database
+        .into_iter()
+        .enumerate()
+        .filter(|(index, regex_input)| args.filter.matches(*index, regex_input))
+        .collect();
+
+    let issues = lint::lint_entries(&entries, bench_all, with_time);
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    println!(
+        "{} problems found in {} entries",
+        issues.len(),
+        entries.len()
+    );
+    Ok(if issues.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(constants::EXIT_TESTS_FAILED)
+    })
+}
+
+/// Prints which of the circom test cases listed in the files are implemented by the database or
+/// by the hardcoded tests. Returns a failure exit code if some of them are missing.
+fn print_circom_coverage(
+    args: &Args,
+    files: &[String],
+    hardcoded: &str,
+) -> anyhow::Result<ExitCode> {
+    let database =
+        RegexDb::load_from_file(Path::new(&args.db)).context("error creating the database")?;
+    let db_entries: Vec<DbEntry> = database.into_iter().collect();
+    let hardcoded_testnames = coverage::hardcoded_testnames(Path::new(hardcoded))?;
+
+    let mut circom_test_cases = Vec::new();
+    for file in files {
+        circom_test_cases.extend(coverage::read_circom_test_cases(Path::new(file))?);
+    }
+
+    let coverage_report =
+        CoverageReport::new(&circom_test_cases, &db_entries, &hardcoded_testnames);
+    println!("{}", coverage_report);
+    Ok(if coverage_report.is_complete() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(constants::EXIT_TESTS_FAILED)
+    })
+}
+
+/// Spreads the entries across `args.jobs` workers, each one with its own isolated workspace,
+/// and returns the reports in the same order as the entries.
+fn process_entries_in_parallel(
+    entries: &[(usize, DbEntry)],
+    args: &Args,
+    benchmark_all: bool,
+) -> anyhow::Result<Vec<EntryReport>> {
+    let workspaces = (0..args.jobs)
+        .map(Workspace::isolated)
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let next_entry = AtomicUsize::new(0);
+
+    let mut entry_reports: Vec<(usize, EntryReport)> = thread::scope(|scope| {
+        let workers: Vec<_> = workspaces
+            .iter()
+            .map(|workspace| {
+                let next_entry = &next_entry;
+                scope.spawn(move || {
+                    let mut worker_reports = Vec::new();
+                    loop {
+                        let position = next_entry.fetch_add(1, Ordering::Relaxed);
+                        let Some((index, regex_input)) = entries.get(position) else {
+                            break;
+                        };
+                        worker_reports.push((
+                            position,
+                            process_entry(*index, regex_input, args, benchmark_all, workspace),
+                        ));
+                    }
+                    worker_reports
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("a worker panicked"))
+            .collect()
+    });
+
+    entry_reports.sort_by_key(|(position, _)| *position);
+    Ok(entry_reports
+        .into_iter()
+        .map(|(_, entry_report)| entry_report)
+        .collect())
+}
+
+/// Generates the code for the entry in the given position of the database in the given
+/// workspace and runs the testing and the benchmarking requested in the arguments.
+fn process_entry(
+    index: usize,
+    regex_input: &DbEntry,
+    args: &Args,
+    benchmark_all: bool,
+    workspace: &Workspace,
+) -> EntryReport {
+    let mut entry_report = EntryReport::new(index, regex_input);
+    if let Some(skip) = &regex_input.skip {
+        info!(
+            "skipping regex {}: {}",
+            regex_input.regex.complete_regex(),
+            skip.reason
+        );
+        return entry_report;
+    }
+    let mut code_read_result = Code::new(regex_input, workspace);
+    match &mut code_read_result {
+        Ok(code) => {
+            if args.test {
+                // The seed of the entry takes precedence, so an entry can be pinned to a seed
+                let seed = regex_input.seed.or(args.seed).unwrap_or_else(rand::random);
+                info!(
+                    "testing regex {} with seed {}",
+                    regex_input.regex.complete_regex(),
+                    seed
+                );
+                entry_report.seed = Some(seed);
+                let test_options = TestOptions {
+                    seed,
+                    shrink: args.shrink,
+                    random_samples: args.random_samples,
+                    random_budget: args.random_budget_secs.map(Duration::from_secs),
+                };
+                match test_regex(regex_input, code, workspace, &test_options) {
+                    Ok(test_result) => {
+                        info!(
+                            "test passed correctly for regex {}:\n{}",
+                            regex_input.regex.complete_regex(),
+                            test_result
+                        );
+                        entry_report.test_result = Some(test_result);
+                    }
+                    Err(err) => match err.downcast() {
+                        Ok(tester::Error::TestFailed(test_result)) => {
+                            match &regex_input.expected_failure {
+                                Some(expected_failure) if expected_failure.samples.is_empty() => {
+                                    info!(
+                                        "test failed as expected for regex {} with seed {} ({}):\n{}",
+                                        regex_input.regex.complete_regex(),
+                                        seed,
+                                        expected_failure.reason,
+                                        test_result
+                                    )
+                                }
+                                _ => error!(
+                                    "test failed for regex {} with seed {}:\n{}",
+                                    regex_input.regex.complete_regex(),
+                                    seed,
+                                    test_result
+                                ),
+                            }
+                            entry_report.test_result = Some(test_result);
+                        }
+                        Err(err) => {
+                            error!(
+                                "error running the test for regex {}: {:?}",
                                 regex_input.regex.complete_regex(),
-                                test_result
+                                err
                             );
+                            entry_report
+                                .errors
+                                .push(format!("error running the test: {:#}", err));
+                        }
+                    },
+                }
+                if entry_report.status() == EntryStatus::UnexpectedPass {
+                    warn!(
+                        "regex {} is expected to fail, but the test passed for {}: update its expected_failure",
+                        regex_input.regex.complete_regex(),
+                        match entry_report
+                            .test_result
+                            .as_ref()
+                            .map(|test_result| test_result.unexpected_passes())
+                        {
+                            Some(samples) if !samples.is_empty() => format!("{:?}", samples),
+                            _ => "all the samples".to_string(),
                         }
-                        Err(err) => match err.downcast_ref() {
-                            Some(tester::Error::TestFailed(test_result)) => {
-                                error!(
-                                    "test failed for regex {}:\n{}",
+                    );
+                }
+                if args.padding {
+                    match test_padding(regex_input, code, workspace, seed) {
+                        Ok(padding_report) => {
+                            if padding_report.differences.is_empty() {
+                                info!(
+                                    "padding test for regex {}: {}",
                                     regex_input.regex.complete_regex(),
-                                    test_result
-                                )
+                                    padding_report
+                                );
+                            } else {
+                                warn!(
+                                    "padding test for regex {}: {}",
+                                    regex_input.regex.complete_regex(),
+                                    padding_report
+                                );
                             }
-                            None => error!("error downcasting the anyhow::Error"),
-                        },
-                    }
-                }
-                if args.bench.is_some() && (regex_input.with_bench || benchmark_all) {
-                    info!("bencmarking regex {}", regex_input.regex.complete_regex());
-                    match benchmark_noir_code(
-                        regex_input.input_size,
-                        regex_input.benchmark_str,
-                        args.bench.as_ref().unwrap(),
-                    ) {
-                        Ok(mut bench_result) => {
-                            info!("benchmark results:\n{}", bench_result);
-                            // Changes the data needed to write the report.
-                            bench_result.regex = regex_input.regex.complete_regex();
-                            bench_result.with_gen_substr = regex_input.gen_substrs;
-                            bench_report.push_result(bench_result);
+                            entry_report.padding_report = Some(padding_report);
                         }
                         Err(err) => {
                             error!(
-                                "error running the benchmark for regex {}: {:?}",
+                                "error running the padding test for regex {}: {:?}",
                                 regex_input.regex.complete_regex(),
                                 err
-                            )
+                            );
+                            entry_report
+                                .errors
+                                .push(format!("error running the padding test: {:#}", err));
                         }
                     }
                 }
+                if let Some(test_result) = &entry_report.test_result {
+                    match Dfa::from_noir_code(code.noir_code()) {
+                        Ok(dfa) => {
+                            let coverage = TransitionCoverage::new(&dfa, regex_input, test_result);
+                            info!(
+                                "transition coverage of the automaton for regex {}:\n{}",
+                                regex_input.regex.complete_regex(),
+                                coverage
+                            );
+                            entry_report.transition_coverage = Some(coverage);
+                        }
+                        Err(err) => info!(
+                            "ignoring the transition coverage due to the following error: {:?}",
+                            err
+                        ),
+                    }
+                }
+            }
+            if let Some(bench_exec_type) = args
+                .command
+                .as_ref()
+                .and_then(Command::bench_exec_type)
+                .filter(|_| regex_input.with_bench || benchmark_all)
+            {
+                info!("bencmarking regex {}", regex_input.regex.complete_regex());
+                // The circuit is compiled in the workspace of the entry without the tests, so
+                // that the compiled program in its `target` folder is the one benchmarked
+                // A benchmarking string that doesn't fit is reported before the compilation
+                let bench_result = match bench_exec_type {
+                    BenchExecType::WithTime => bench::check_benchmark_str(
+                        regex_input.input_size,
+                        &regex_input.benchmark_str,
+                    ),
+                    BenchExecType::NoTime => Ok(()),
+                }
+                .and_then(|_| code.write_to_path(&workspace.project_main_file()))
+                .and_then(|_| compiler::compile_noir_project(workspace))
+                .and_then(|_| {
+                    benchmark_noir_code(
+                        regex_input.input_size,
+                        regex_input.benchmark_str.clone(),
+                        &bench_exec_type,
+                        workspace,
+                    )
+                });
+                match bench_result {
+                    Ok(mut bench_result) => {
+                        info!("benchmark results:\n{}", bench_result);
+                        // Changes the data needed to write the report.
+                        bench_result.regex = regex_input.regex.complete_regex();
+                        bench_result.with_gen_substr = regex_input.gen_substrs;
+                        bench_result.input_size = regex_input.input_size;
+                        entry_report.bench_result = Some(bench_result);
+                    }
+                    Err(err) => {
+                        error!(
+                            "error running the benchmark for regex {}: {:?}",
+                            regex_input.regex.complete_regex(),
+                            err
+                        );
+                        entry_report
+                            .errors
+                            .push(format!("error running the benchmark: {:#}", err));
+                    }
+                }
             }
-            Err(err) => match err.downcast_ref() {
+        }
+        Err(err) => {
+            match err.downcast_ref() {
                 Some(code::Error::CodeGenerationFailed(console_msg)) => {
                     error!("error generating the code: \n{}", console_msg);
                 }
-                None => error!("error downcasting the anyhow::Error"),
-            },
+                None => error!("error generating the code: {:?}", err),
+            }
+            entry_report
+                .errors
+                .push(format!("error generating the code: {:#}", err));
         }
     }
-
-    // Save the bench results.
-    if !bench_report.is_empty() {
-        info!("saving benchmark results into CSV");
-        bench_report.save(Path::new(constants::DEFAULT_BENCH_RESULT_FILE))?;
-    }
-
-    Ok(())
+    entry_report
 }
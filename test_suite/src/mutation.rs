@@ -0,0 +1,176 @@
+use anyhow::Context;
+use itertools::Itertools;
+use rand::{seq::SliceRandom, Rng};
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+use crate::bytes::ByteString;
+
+/// Change applied to a passing sample to obtain an input that is close to the language of the
+/// regex, but likely outside of it.
+#[derive(Clone, Copy, Debug)]
+enum Mutation {
+    /// Flips one bit of a byte.
+    FlipByte,
+    /// Inserts a character at a random position.
+    Insert,
+    /// Deletes a character.
+    Delete,
+    /// Keeps only a prefix of the sample.
+    Truncate,
+    /// Repeats a segment of the sample right after itself.
+    DuplicateSegment,
+    /// Replaces a character with a character just outside one of the character classes, or next
+    /// to a character of a literal.
+    OutsideClass,
+}
+
+const MUTATIONS: [Mutation; 6] = [
+    Mutation::FlipByte,
+    Mutation::Insert,
+    Mutation::Delete,
+    Mutation::Truncate,
+    Mutation::DuplicateSegment,
+    Mutation::OutsideClass,
+];
+
+/// Generator of mutants of the passing samples of a regex.
+pub struct Mutator {
+    /// Characters right before and right after each range of the character classes of the
+    /// regex that are not in the class themselves, and around each character of the literals.
+    boundary_chars: Vec<char>,
+}
+
+impl Mutator {
+    /// Creates the generator for the given regex.
+    pub fn new(regex: &str) -> anyhow::Result<Self> {
+        let hir = regex_syntax::ParserBuilder::new()
+            .utf8(false)
+            .build()
+            .parse(regex)
+            .context("error parsing the regex for the mutations")?;
+        let mut boundary_chars = Vec::new();
+        collect_boundary_chars(&hir, &mut boundary_chars);
+        Ok(Self {
+            boundary_chars: boundary_chars.into_iter().unique().collect(),
+        })
+    }
+
+    /// Generates `count` mutants of randomly chosen samples. Mutants that are empty or longer
+    /// than `max_len` bytes are discarded, so fewer mutants may be returned.
+    pub fn mutants<R: Rng>(
+        &self,
+        samples: &[ByteString],
+        count: usize,
+        max_len: usize,
+        rng: &mut R,
+    ) -> Vec<ByteString> {
+        if samples.is_empty() {
+            return Vec::new();
+        }
+        (0..count)
+            .filter_map(|_| {
+                let sample = samples.choose(rng)?;
+                let mutant = self.mutate(sample, rng);
+                (!mutant.is_empty() && mutant.len() <= max_len).then_some(mutant)
+            })
+            .collect()
+    }
+
+    /// Applies a random mutation to the sample. The sample is mutated character by character,
+    /// where the bytes that are not valid UTF-8 count as single characters.
+    fn mutate<R: Rng>(&self, sample: &ByteString, rng: &mut R) -> ByteString {
+        let mut chars: Vec<Vec<u8>> = sample.chars().into_iter().map(<[u8]>::to_vec).collect();
+        let mutation = *MUTATIONS.choose(rng).expect("there are mutations");
+        if chars.is_empty() {
+            chars.push(self.random_char(rng));
+            return chars.concat().into();
+        }
+        let position = rng.gen_range(0..chars.len());
+        match mutation {
+            Mutation::FlipByte => {
+                chars[position] = match chars[position][..] {
+                    [byte] if byte.is_ascii() => vec![byte ^ (1 << rng.gen_range(0..7))],
+                    _ => vec![rng.gen_range(b' '..=b'~')],
+                }
+            }
+            Mutation::Insert => chars.insert(rng.gen_range(0..=chars.len()), self.random_char(rng)),
+            Mutation::Delete => {
+                chars.remove(position);
+            }
+            Mutation::Truncate => chars.truncate(position),
+            Mutation::DuplicateSegment => {
+                let end = rng.gen_range(position + 1..=chars.len());
+                let segment = chars[position..end].to_vec();
+                chars.splice(end..end, segment);
+            }
+            Mutation::OutsideClass => {
+                if let Some(&c) = self.boundary_chars.choose(rng) {
+                    chars[position] = c.to_string().into_bytes();
+                }
+            }
+        }
+        chars.concat().into()
+    }
+
+    /// Returns the UTF-8 bytes of a printable ASCII character or a character just outside a
+    /// class of the regex.
+    fn random_char<R: Rng>(&self, rng: &mut R) -> Vec<u8> {
+        let c = match self.boundary_chars.choose(rng) {
+            Some(&c) if rng.gen_bool(0.5) => c,
+            _ => rng.gen_range(' '..='~'),
+        };
+        c.to_string().into_bytes()
+    }
+}
+
+/// Collects the characters right before and right after each range of the character classes
+/// and each character of the literals.
+fn collect_boundary_chars(hir: &Hir, boundary_chars: &mut Vec<char>) {
+    match hir.kind() {
+        HirKind::Literal(literal) => {
+            for c in String::from_utf8_lossy(&literal.0).chars() {
+                let before = (c as u32).checked_sub(1).and_then(char::from_u32);
+                let after = char::from_u32(c as u32 + 1);
+                boundary_chars.extend([before, after].into_iter().flatten());
+            }
+        }
+        HirKind::Class(Class::Unicode(class)) => {
+            for range in class.ranges() {
+                let before = (range.start() as u32)
+                    .checked_sub(1)
+                    .and_then(char::from_u32);
+                let after = char::from_u32(range.end() as u32 + 1);
+                boundary_chars.extend([before, after].into_iter().flatten().filter(|c| {
+                    !class
+                        .ranges()
+                        .iter()
+                        .any(|r| (r.start()..=r.end()).contains(c))
+                }));
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            for range in class.ranges() {
+                let before = range.start().checked_sub(1);
+                let after = range.end().checked_add(1);
+                boundary_chars.extend(
+                    [before, after]
+                        .into_iter()
+                        .flatten()
+                        .filter(|b| b.is_ascii())
+                        .filter(|b| {
+                            !class
+                                .ranges()
+                                .iter()
+                                .any(|r| (r.start()..=r.end()).contains(b))
+                        })
+                        .map(char::from),
+                );
+            }
+        }
+        kind => {
+            for sub in kind.subs() {
+                collect_boundary_chars(sub, boundary_chars);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::ops::Range;
+
+use anyhow::Context;
+use regex_syntax::hir::Look;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Rule that decides whether an input matches the regex, which depends on how the circuit
+/// uses the regex.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// The regex matches anywhere in the input.
+    #[default]
+    Search,
+    /// The match must start at the beginning of the input.
+    AnchoredStart,
+    /// The regex must match the whole input.
+    FullMatch,
+    /// The match must end at the end of the input, without counting the zero bytes that pad
+    /// the input up to the input size.
+    EndAnchored,
+}
+
+impl MatchMode {
+    /// Whether this is the default match mode, which is left out when an entry is written.
+    pub fn is_search(&self) -> bool {
+        *self == MatchMode::Search
+    }
+}
+
+/// Ground truth of the tests: decides with the Rust regex lib whether an input should be
+/// accepted by the circuit.
+pub struct Oracle {
+    /// Regex anchored according to the match mode, which matches the bytes of the inputs.
+    regex: regex::bytes::Regex,
+    /// Match mode of the entry.
+    mode: MatchMode,
+    /// Whether a match must start at the beginning of the input.
+    anchored_start: bool,
+}
+
+impl Oracle {
+    /// Creates the oracle for a regex and the match mode of its entry.
+    pub fn new(regex: &str, mode: MatchMode) -> anyhow::Result<Self> {
+        let anchored_regex = match mode {
+            MatchMode::Search => regex.to_string(),
+            MatchMode::AnchoredStart => format!(r"\A(?:{})", regex),
+            MatchMode::FullMatch => format!(r"\A(?:{})\z", regex),
+            MatchMode::EndAnchored => format!(r"(?:{})\z", regex),
+        };
+        let regex = regex::bytes::Regex::new(&anchored_regex)
+            .context("error parsing the regex in the ground truth checker")?;
+        // A start anchor in any of the alternatives at the beginning, as in `(\r\n|^)`, is
+        // enough to make the zero bytes before the input change the outcome
+        let prefix_looks = regex_syntax::ParserBuilder::new()
+            .utf8(false)
+            .build()
+            .parse(&anchored_regex)
+            .context("error parsing the regex in the ground truth checker")?
+            .properties()
+            .look_set_prefix_any();
+        let anchored_start = [Look::Start, Look::StartLF, Look::StartCRLF]
+            .into_iter()
+            .any(|look| prefix_looks.contains(look));
+        Ok(Self {
+            regex,
+            mode,
+            anchored_start,
+        })
+    }
+
+    /// Whether a match must start at the beginning of the input, either because of the match
+    /// mode or because of a start anchor in the regex.
+    pub fn is_anchored_start(&self) -> bool {
+        self.anchored_start
+    }
+
+    /// Whether the circuit should accept the input.
+    pub fn accepts(&self, input: &[u8]) -> bool {
+        self.regex.is_match(self.trimmed(input))
+    }
+
+    /// Returns the positions of the successive non-overlapping matches of the regex in the
+    /// input.
+    pub fn match_ranges(&self, input: &[u8]) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(self.trimmed(input))
+            .map(|found| found.range())
+            .collect()
+    }
+
+    /// Returns the part of the input the regex is matched against: the input without the zero
+    /// bytes at the end in the `end_anchored` mode, and the whole input otherwise.
+    fn trimmed<'a>(&self, input: &'a [u8]) -> &'a [u8] {
+        match self.mode {
+            MatchMode::EndAnchored => {
+                let end = input
+                    .iter()
+                    .rposition(|&byte| byte != 0)
+                    .map_or(0, |i| i + 1);
+                &input[..end]
+            }
+            _ => input,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anchors_the_regex_according_to_the_match_mode() {
+        let search = Oracle::new("ab", MatchMode::Search).unwrap();
+        assert!(search.accepts(b"xaby"));
+
+        let anchored_start = Oracle::new("ab", MatchMode::AnchoredStart).unwrap();
+        assert!(anchored_start.accepts(b"aby"));
+        assert!(!anchored_start.accepts(b"xab"));
+
+        let full_match = Oracle::new("a|ab", MatchMode::FullMatch).unwrap();
+        assert!(full_match.accepts(b"ab"));
+        assert!(!full_match.accepts(b"abc"));
+    }
+
+    #[test]
+    fn finds_the_start_anchors_of_the_regex() {
+        let anchored = |regex, mode| Oracle::new(regex, mode).unwrap().is_anchored_start();
+        assert!(anchored("ab", MatchMode::AnchoredStart));
+        assert!(anchored("^ab", MatchMode::Search));
+        assert!(anchored(r"(\r\n|^)subject:", MatchMode::Search));
+        assert!(!anchored("ab", MatchMode::Search));
+        assert!(!anchored("ab", MatchMode::EndAnchored));
+    }
+
+    #[test]
+    fn ignores_the_zero_padding_at_the_end() {
+        let oracle = Oracle::new("ab", MatchMode::EndAnchored).unwrap();
+        assert!(oracle.accepts(b"xab\0\0\0"));
+        assert!(!oracle.accepts(b"abx\0"));
+        assert!(!oracle.accepts(b"ab\0x"));
+    }
+
+    #[test]
+    fn matches_the_bytes_of_the_input() {
+        let oracle = Oracle::new(r"(?-u)a\xff", MatchMode::FullMatch).unwrap();
+        assert!(oracle.accepts(b"a\xff"));
+        assert!(!oracle.accepts("aÿ".as_bytes()));
+    }
+}
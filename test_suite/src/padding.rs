@@ -0,0 +1,269 @@
+use std::fmt::{self, Display};
+
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{bytes::ByteString, code::NoirTest, constants, oracle::Oracle};
+
+/// Way in which a sample is laid out in an input of the size of the circuit.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaddingVariant {
+    /// The sample ends at the last byte of the input, with zero bytes before it.
+    LengthExact,
+    /// The sample is followed by zero bytes up to the input size.
+    ZeroPadded,
+    /// The sample is followed by a few random printable characters and then by zero bytes up to
+    /// the input size.
+    TrailingGarbage,
+}
+
+impl Display for PaddingVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaddingVariant::LengthExact => write!(f, "length-exact"),
+            PaddingVariant::ZeroPadded => write!(f, "zero-padded"),
+            PaddingVariant::TrailingGarbage => write!(f, "trailing garbage"),
+        }
+    }
+}
+
+impl PaddingVariant {
+    /// Returns the input of the variant of the sample for the given input size, or `None` if
+    /// the variant can't be built. The length-exact variant is not built when
+    /// `anchored_start` is set, since the zero bytes before the sample change the expected
+    /// outcome.
+    pub fn apply<R: Rng>(
+        &self,
+        input: &[u8],
+        input_size: usize,
+        anchored_start: bool,
+        rng: &mut R,
+    ) -> Option<ByteString> {
+        let room = input_size
+            .checked_sub(input.len())
+            .filter(|&room| room > 0)?;
+        let variant_input = match self {
+            PaddingVariant::LengthExact if anchored_start => return None,
+            PaddingVariant::LengthExact => [&vec![0; room][..], input].concat(),
+            PaddingVariant::ZeroPadded => [input, &vec![0; room][..]].concat(),
+            PaddingVariant::TrailingGarbage => {
+                let garbage_len = room.min(constants::DEFAULT_GARBAGE_LEN);
+                let garbage: Vec<u8> = (0..garbage_len)
+                    .map(|_| rng.gen_range(b'!'..=b'~'))
+                    .collect();
+                [input, &garbage, &vec![0; room - garbage_len]].concat()
+            }
+        };
+        Some(variant_input.into())
+    }
+}
+
+/// Returns the test of a variant of a sample, which has the same expected outcome and
+/// substrings as the test of the sample. The trailing garbage can make the regex match, or
+/// extend the match, so the expected outcome of that variant is decided again by the oracle,
+/// and a variant with substrings is only tested if the regex matches the same parts of it as
+/// of the sample. Returns `None` if the variant is not tested.
+pub fn variant_test(
+    test: &NoirTest,
+    variant: PaddingVariant,
+    variant_input: ByteString,
+    oracle: &Oracle,
+) -> Option<NoirTest> {
+    match test {
+        NoirTest::Standard { should_fail, .. } => Some(NoirTest::Standard {
+            should_fail: match variant {
+                PaddingVariant::TrailingGarbage => !oracle.accepts(&variant_input),
+                _ => *should_fail,
+            },
+            input: variant_input,
+        }),
+        NoirTest::Substring(sample) => {
+            if variant == PaddingVariant::TrailingGarbage
+                && oracle.match_ranges(&variant_input) != oracle.match_ranges(&sample.input)
+            {
+                log::info!(
+                    "the trailing garbage variant of {:?} is not tested because the garbage changes the match",
+                    sample.input
+                );
+                return None;
+            }
+            let mut sample = sample.clone();
+            sample.input = variant_input;
+            Some(NoirTest::Substring(sample))
+        }
+    }
+}
+
+/// Returns the input followed by zero bytes up to the input size, which is what the circuit
+/// receives for a sample when the inputs are padded, as in the sweep of input sizes.
+pub fn zero_padded(input: &[u8], input_size: usize) -> Vec<u8> {
+    let mut padded = input.to_vec();
+    if padded.len() < input_size {
+        padded.resize(input_size, 0);
+    }
+    padded
+}
+
+/// Returns the test of a sample as it is run when its input is padded with zeros up to the
+/// input size, which is labeled by the oracle on the padded bytes when the zero bytes change
+/// the outcome (e.g. with the `full_match` mode, a `$` anchor or a class that accepts `\0`):
+/// - a standard test gets the expected outcome of the padded input.
+/// - a test with substrings of an input that is rejected once padded becomes a standard test
+///   that should fail, and it is not tested if the zero bytes change the match otherwise.
+///
+/// The input of the returned test is still the unpadded one, which the code pads when it
+/// writes the test. Returns `None` if the test is not run.
+pub fn padded_test(test: NoirTest, input_size: usize, oracle: &Oracle) -> Option<NoirTest> {
+    let padded = zero_padded(test.input(), input_size);
+    match test {
+        NoirTest::Standard { input, should_fail } => Some(NoirTest::Standard {
+            should_fail: if oracle.accepts(&padded) != oracle.accepts(&input) {
+                !oracle.accepts(&padded)
+            } else {
+                should_fail
+            },
+            input,
+        }),
+        NoirTest::Substring(sample)
+            if oracle.accepts(&sample.input) && !oracle.accepts(&padded) =>
+        {
+            Some(NoirTest::Standard {
+                input: sample.input,
+                should_fail: true,
+            })
+        }
+        NoirTest::Substring(sample) => {
+            if oracle.match_ranges(&padded) != oracle.match_ranges(&sample.input) {
+                log::info!(
+                    "the sample {:?} is not tested because the zero bytes that pad it change the match",
+                    sample.input
+                );
+                return None;
+            }
+            Some(NoirTest::Substring(sample))
+        }
+    }
+}
+
+/// All the padding variants.
+pub const PADDING_VARIANTS: [PaddingVariant; 3] = [
+    PaddingVariant::LengthExact,
+    PaddingVariant::ZeroPadded,
+    PaddingVariant::TrailingGarbage,
+];
+
+/// A sample whose outcome in the circuit changes when it is padded.
+#[derive(Serialize, Debug)]
+pub struct PaddingDifference {
+    /// Input of the sample.
+    pub input: ByteString,
+    /// Variant of the sample that has a different outcome.
+    pub variant: PaddingVariant,
+    /// Input of the variant.
+    pub variant_input: ByteString,
+    /// Whether the test of the sample passed.
+    pub sample_passed: bool,
+    /// Console output of `nargo test` for the test of the variant.
+    pub nargo_output: String,
+}
+
+/// Result of testing the padding variants of the samples of an entry.
+#[derive(Serialize, Debug)]
+pub struct PaddingReport {
+    /// Number of variants that were tested.
+    pub tested: usize,
+    /// Variants whose outcome differs from the outcome of their sample.
+    pub differences: Vec<PaddingDifference>,
+}
+
+impl Display for PaddingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} padding variants changed the outcome of their sample",
+            self.differences.len(),
+            self.tested
+        )?;
+        for difference in &self.differences {
+            writeln!(
+                f,
+                "  {:?}: the test {} for the sample but {} for its {} variant",
+                difference.input,
+                if difference.sample_passed {
+                    "passed"
+                } else {
+                    "failed"
+                },
+                if difference.sample_passed {
+                    "failed"
+                } else {
+                    "passed"
+                },
+                difference.variant
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::InputWithSubstrs, oracle::MatchMode};
+
+    fn standard(input: &str, should_fail: bool) -> NoirTest {
+        NoirTest::Standard {
+            input: input.into(),
+            should_fail,
+        }
+    }
+
+    fn substring(input: &str, expected_substring: &str) -> NoirTest {
+        NoirTest::Substring(InputWithSubstrs {
+            input: input.into(),
+            expected_substrings: vec![expected_substring.into()],
+            circom_testname: None,
+        })
+    }
+
+    fn should_fail(test: Option<NoirTest>) -> Option<bool> {
+        match test? {
+            NoirTest::Standard { should_fail, .. } => Some(should_fail),
+            NoirTest::Substring(_) => None,
+        }
+    }
+
+    #[test]
+    fn labels_the_padded_inputs() {
+        let full_match = Oracle::new("ab", MatchMode::FullMatch).unwrap();
+        assert_eq!(
+            should_fail(padded_test(standard("ab", false), 4, &full_match)),
+            Some(true)
+        );
+        // The zero bytes don't change the outcome, so the given label is kept
+        assert_eq!(
+            should_fail(padded_test(standard("ba", false), 4, &full_match)),
+            Some(false)
+        );
+        let with_zeros = Oracle::new(r"a\x00", MatchMode::Search).unwrap();
+        assert_eq!(
+            should_fail(padded_test(standard("a", true), 4, &with_zeros)),
+            Some(false)
+        );
+
+        let end_anchor = Oracle::new("b$", MatchMode::Search).unwrap();
+        assert_eq!(
+            should_fail(padded_test(substring("ab", "b"), 4, &end_anchor)),
+            Some(true)
+        );
+        let search = Oracle::new("b", MatchMode::Search).unwrap();
+        assert!(matches!(
+            padded_test(substring("ab", "b"), 4, &search),
+            Some(NoirTest::Substring(_))
+        ));
+        // The zero bytes extend the match, so the expected substrings are unknown
+        let extended = Oracle::new(r"b[^c]*", MatchMode::Search).unwrap();
+        assert!(padded_test(substring("ab", "b"), 4, &extended).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bytes::ByteString,
+    db::{DbEntry, EntrySource, InputWithSubstrs, SamplesPass},
+    report::{EntryReport, EntryStatus},
+    tester::{FailureCategory, FailureCause, SampleOrigin},
+};
+
+/// Samples of the random testing that were judged incorrectly, written as samples of their
+/// entries so that they can be added to the database as regression tests.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct FailurePatch {
+    /// The recorded failures, in the order of the entries.
+    pub failures: Vec<RecordedFailure>,
+}
+
+/// A sample that was judged incorrectly, written as a passing or failing sample of its entry.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedFailure {
+    /// Database file and position of the entry.
+    pub source: EntrySource,
+    /// Complete regex of the entry, used to check that the entry didn't change before the
+    /// merge.
+    pub regex: String,
+    /// How the sample was obtained.
+    pub origin: SampleOrigin,
+    /// Seed of the random samples of the entry in the run that found the failure.
+    pub seed: u64,
+    /// Input size at which the failure was found.
+    pub input_size: usize,
+    /// Classification of the failure.
+    pub cause: FailureCause,
+    /// The sample, if it should match the regex.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub samples_pass: Option<SamplesPass>,
+    /// The sample, if it shouldn't match the regex.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples_fail: Vec<ByteString>,
+}
+
+impl RecordedFailure {
+    /// Returns the input of the recorded sample.
+    fn input(&self) -> Option<&ByteString> {
+        match &self.samples_pass {
+            Some(SamplesPass::WithSubstrs(samples)) => samples.first().map(|sample| &sample.input),
+            Some(SamplesPass::WithoutSubstrs(samples)) => samples.first(),
+            None => self.samples_fail.first(),
+        }
+    }
+}
+
+impl FailurePatch {
+    /// Records the failures of the samples that are not given in the entries: the random
+    /// samples, the mutants and the inputs of the uncovered transitions. The entries are given
+    /// together with their reports, in the same order. A shrunk sample is recorded with its
+    /// minimal input, except for the samples with incorrect substrings, whose minimal input has
+    /// unknown substrings. The entries that are expected to fail as a whole are not recorded.
+    pub fn new(entries: &[(usize, DbEntry)], reports: &[EntryReport]) -> Self {
+        let mut patch = Self::default();
+        for ((_, entry), report) in entries.iter().zip(reports) {
+            if report.status() == EntryStatus::ExpectedFailure {
+                continue;
+            }
+            let Some(test_result) = &report.test_result else {
+                continue;
+            };
+            for (category, sample) in test_result.failed_tests() {
+                if sample.origin == SampleOrigin::Given {
+                    continue;
+                }
+                let input = sample.minimal_input.as_ref().unwrap_or(&sample.input);
+                let (samples_pass, samples_fail) = match category {
+                    FailureCategory::FalsePositive => (None, vec![input.clone()]),
+                    FailureCategory::FalseNegative => (
+                        Some(SamplesPass::WithoutSubstrs(vec![input.clone()])),
+                        Vec::new(),
+                    ),
+                    FailureCategory::IncorrectSubstrings => (
+                        Some(SamplesPass::WithSubstrs(vec![InputWithSubstrs {
+                            input: sample.input.clone(),
+                            expected_substrings: sample
+                                .expected_substrings
+                                .clone()
+                                .unwrap_or_default(),
+                            circom_testname: None,
+                        }])),
+                        Vec::new(),
+                    ),
+                };
+                let failure = RecordedFailure {
+                    source: entry.source.clone(),
+                    regex: report.regex.clone(),
+                    origin: sample.origin,
+                    seed: report.seed.unwrap_or_default(),
+                    input_size: report.input_size,
+                    cause: sample.cause,
+                    samples_pass,
+                    samples_fail,
+                };
+                // The same sample can be found again in another round or at another size
+                if !patch.failures.iter().any(|recorded| {
+                    recorded.source == failure.source && recorded.input() == failure.input()
+                }) {
+                    patch.failures.push(failure);
+                }
+            }
+        }
+        patch
+    }
+
+    /// Reads a patch from a JSON file.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)
+            .context(format!("error reading the recorded failures {:?}", path))?;
+        serde_json::from_str(&contents)
+            .context(format!("error parsing the recorded failures {:?}", path))
+    }
+
+    /// Saves the patch as a JSON file in the given path.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .context("error serializing the recorded failures")?;
+        fs::write(path, contents + "\n").context(format!(
+            "error writing the recorded failures to the path {:?}",
+            path
+        ))?;
+        Ok(())
+    }
+
+    /// Adds the recorded samples to their entries of the database and returns the number of
+    /// added samples. A failure is not added, with a warning, if its entry is not found, if the
+    /// regex of the entry changed, if the sample is longer than the input size of the entry, or
+    /// if its expected substrings don't fit the samples of the entry. Samples that are already
+    /// in the entry are not added again.
+    pub fn merge_into(self, database: &mut [DbEntry]) -> usize {
+        let mut added = 0;
+        for failure in self.failures {
+            let Some(entry) = database
+                .iter_mut()
+                .find(|entry| entry.source.is_same_entry(&failure.source))
+            else {
+                log::warn!(
+                    "the entry {} of the recorded failure is not in the database",
+                    failure.source
+                );
+                continue;
+            };
+            if entry.regex.complete_regex() != failure.regex {
+                log::warn!(
+                    "the regex of the entry {} changed since the failure was recorded, so it is not added",
+                    failure.source
+                );
+                continue;
+            }
+            if failure
+                .input()
+                .is_some_and(|input| input.len() > entry.input_size)
+            {
+                log::warn!(
+                    "the recorded sample {:?} is longer than the input size of the entry {}, so it is not added",
+                    failure.input(),
+                    failure.source
+                );
+                continue;
+            }
+            if merge_failure(entry, failure) {
+                added += 1;
+            }
+        }
+        added
+    }
+}
+
+/// Adds the sample of a recorded failure to the entry, unless it is already in it. Returns
+/// whether the sample was added.
+fn merge_failure(entry: &mut DbEntry, failure: RecordedFailure) -> bool {
+    let Some(samples_pass) = failure.samples_pass else {
+        let mut added = false;
+        for sample in failure.samples_fail {
+            if !entry.samples_fail.contains(&sample) {
+                entry.samples_fail.push(sample);
+                added = true;
+            }
+        }
+        return added;
+    };
+    let pass_inputs: Vec<ByteString> = entry.pass_inputs().into_iter().cloned().collect();
+    match (&mut entry.samples_pass, samples_pass) {
+        (SamplesPass::WithSubstrs(samples), SamplesPass::WithSubstrs(new_samples)) => {
+            let new_samples: Vec<_> = new_samples
+                .into_iter()
+                .filter(|sample| !pass_inputs.contains(&sample.input))
+                .collect();
+            let added = !new_samples.is_empty();
+            samples.extend(new_samples);
+            added
+        }
+        (SamplesPass::WithoutSubstrs(samples), SamplesPass::WithoutSubstrs(new_samples)) => {
+            let new_samples: Vec<_> = new_samples
+                .into_iter()
+                .filter(|sample| !pass_inputs.contains(sample))
+                .collect();
+            let added = !new_samples.is_empty();
+            samples.extend(new_samples);
+            added
+        }
+        // An empty list of samples is read as samples with substrings
+        (SamplesPass::WithSubstrs(samples), new_samples) if samples.is_empty() => {
+            entry.samples_pass = new_samples;
+            true
+        }
+        (_, new_samples) => {
+            log::warn!(
+                "the recorded sample {:?} doesn't have the same form as the passing samples of the entry {}, so it is not added",
+                new_samples,
+                entry.source
+            );
+            false
+        }
+    }
+}
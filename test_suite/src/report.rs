@@ -0,0 +1,325 @@
+use std::{
+    fmt::{self, Display},
+    fs,
+    path::Path,
+    process::ExitCode,
+};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::{
+    bench::BenchResult,
+    constants,
+    db::{DbEntry, ExpectedFailure, Skip},
+    dfa_coverage::TransitionCoverage,
+    padding::PaddingReport,
+    tester::TestResult,
+};
+
+/// Final status of a database entry after the run.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryStatus {
+    /// All the requested steps finished and all the tests passed.
+    Passed,
+    /// At least one test sample failed.
+    Failed,
+    /// A step could not be executed (missing tools, code generation failures, etc.).
+    Error,
+    /// The entry has an expected failure, and it failed as expected.
+    #[serde(rename = "xfail")]
+    ExpectedFailure,
+    /// The entry has an expected failure, but the entry or some of the samples of the
+    /// expected failure were judged correctly, so the annotation can be removed or updated.
+    #[serde(rename = "xpass")]
+    UnexpectedPass,
+    /// The entry is marked to be skipped and was not processed.
+    Skipped,
+}
+
+impl Display for EntryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryStatus::Passed => write!(f, "PASSED"),
+            EntryStatus::Failed => write!(f, "FAILED"),
+            EntryStatus::Error => write!(f, "ERROR"),
+            EntryStatus::ExpectedFailure => write!(f, "XFAIL"),
+            EntryStatus::UnexpectedPass => write!(f, "XPASS"),
+            EntryStatus::Skipped => write!(f, "SKIPPED"),
+        }
+    }
+}
+
+/// Results of testing and benchmarking a single database entry.
+#[derive(Serialize, Default)]
+pub struct EntryReport {
+    /// Position of the entry in the database.
+    #[serde(skip)]
+    pub index: usize,
+    /// Name of the entry (`circuit_name` or `test_name`), if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Database file and position of the entry, e.g. `regex_db.json#/database/3`.
+    pub source: String,
+    /// Complete regex of the entry.
+    pub regex: String,
+    /// Input size at which the entry was processed.
+    pub input_size: usize,
+    /// Seed of the random samples, if the entry was tested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Result of the test, if the entry was tested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_result: Option<TestResult>,
+    /// Coverage of the automaton by the tested samples, if the entry was tested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_coverage: Option<TransitionCoverage>,
+    /// Result of testing the padding variants of the samples, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_report: Option<PaddingReport>,
+    /// Result of the benchmark, if the entry was benchmarked successfully.
+    #[serde(skip)]
+    pub bench_result: Option<BenchResult>,
+    /// Errors that prevented the code generation, the testing or the benchmarking.
+    pub errors: Vec<String>,
+    /// Expected failure of the entry, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_failure: Option<ExpectedFailure>,
+    /// Why the entry was skipped, if it is marked to be skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip: Option<Skip>,
+}
+
+impl EntryReport {
+    /// Creates an empty report for the entry in the given position of the database.
+    pub fn new(index: usize, regex_input: &DbEntry) -> Self {
+        Self {
+            index,
+            name: regex_input.name().map(str::to_string),
+            source: regex_input.source.to_string(),
+            regex: regex_input.regex.complete_regex(),
+            input_size: regex_input.input_size,
+            expected_failure: regex_input.expected_failure.clone(),
+            skip: regex_input.skip.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Name used to identify the entry in the reports.
+    pub fn display_name(&self) -> String {
+        entry_display_name(self.name.as_deref(), self.index)
+    }
+
+    /// Returns the status of the entry. Errors take precedence over failed tests. With an
+    /// expected failure for the whole entry, the failed tests are expected and the passed tests
+    /// are unexpected. With an expected failure for some samples, the other samples decide
+    /// whether the entry failed, and a sample of the expected failure that passed makes the
+    /// entry an unexpected pass.
+    pub fn status(&self) -> EntryStatus {
+        if self.skip.is_some() {
+            return EntryStatus::Skipped;
+        }
+        if !self.errors.is_empty() {
+            return EntryStatus::Error;
+        }
+        let Some(test_result) = &self.test_result else {
+            return EntryStatus::Passed;
+        };
+        match &self.expected_failure {
+            None if test_result.passed() => EntryStatus::Passed,
+            None => EntryStatus::Failed,
+            Some(expected_failure) if expected_failure.samples.is_empty() => {
+                if test_result.passed() {
+                    EntryStatus::UnexpectedPass
+                } else {
+                    EntryStatus::ExpectedFailure
+                }
+            }
+            Some(_) if !test_result.passed() => EntryStatus::Failed,
+            Some(_) if !test_result.unexpected_passes().is_empty() => EntryStatus::UnexpectedPass,
+            Some(_) => EntryStatus::ExpectedFailure,
+        }
+    }
+}
+
+/// Name used to identify an entry with the given name in the reports, given its position in
+/// the database.
+pub fn entry_display_name(name: Option<&str>, index: usize) -> String {
+    match name {
+        Some(name) => format!("{} (entry {})", name, index),
+        None => format!("entry {}", index),
+    }
+}
+
+/// Summary of a whole run over the database.
+pub struct RunSummary {
+    /// Reports of the processed entries in the same order as the database.
+    entries: Vec<EntryReport>,
+}
+
+/// Serializable view of the summary, with the status of each entry.
+#[derive(Serialize)]
+struct RunSummaryJson<'a> {
+    passed: usize,
+    failed: usize,
+    errors: usize,
+    xfailed: usize,
+    xpassed: usize,
+    skipped: usize,
+    entries: Vec<EntrySummaryJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct EntrySummaryJson<'a> {
+    index: usize,
+    status: EntryStatus,
+    #[serde(flatten)]
+    report: &'a EntryReport,
+}
+
+impl RunSummary {
+    /// Creates a summary from the reports of the entries.
+    pub fn new(entries: Vec<EntryReport>) -> Self {
+        Self { entries }
+    }
+
+    /// Returns the reports of the entries.
+    pub fn entries(&self) -> &[EntryReport] {
+        &self.entries
+    }
+
+    /// Number of entries with the given status.
+    pub fn count(&self, status: EntryStatus) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.status() == status)
+            .count()
+    }
+
+    /// Exit code of the process: infrastructure errors take precedence over test failures.
+    /// Expected failures, unexpected passes and skipped entries don't fail the run.
+    pub fn exit_code(&self) -> ExitCode {
+        if self.count(EntryStatus::Error) > 0 {
+            ExitCode::from(constants::EXIT_INFRASTRUCTURE_ERROR)
+        } else if self.count(EntryStatus::Failed) > 0 {
+            ExitCode::from(constants::EXIT_TESTS_FAILED)
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+
+    /// Saves the summary as a JSON file in the given path.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let summary = RunSummaryJson {
+            passed: self.count(EntryStatus::Passed),
+            failed: self.count(EntryStatus::Failed),
+            errors: self.count(EntryStatus::Error),
+            xfailed: self.count(EntryStatus::ExpectedFailure),
+            xpassed: self.count(EntryStatus::UnexpectedPass),
+            skipped: self.count(EntryStatus::Skipped),
+            entries: self
+                .entries
+                .iter()
+                .map(|report| EntrySummaryJson {
+                    index: report.index,
+                    status: report.status(),
+                    report,
+                })
+                .collect(),
+        };
+        let contents =
+            serde_json::to_string_pretty(&summary).context("error serializing the summary")?;
+        fs::write(path, contents)
+            .context(format!("error writing the summary to the path {:?}", path))?;
+        Ok(())
+    }
+}
+
+impl Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5}  {:<7}  {:>6}  {:>7}  {:>7}  {:>7}  {:>20}  regex",
+            "entry", "status", "size", "correct", "wrong", "skipped", "seed"
+        )?;
+        for entry in &self.entries {
+            let (correct, wrong, skipped) = entry
+                .test_result
+                .as_ref()
+                .map(|test_result| {
+                    (
+                        test_result.successful_count().to_string(),
+                        test_result.failed_count().to_string(),
+                        test_result.skipped_tests().len().to_string(),
+                    )
+                })
+                .unwrap_or_else(|| ("-".to_string(), "-".to_string(), "-".to_string()));
+            writeln!(
+                f,
+                "{:>5}  {:<7}  {:>6}  {:>7}  {:>7}  {:>7}  {:>20}  {}",
+                entry.index,
+                entry.status(),
+                entry.input_size,
+                correct,
+                wrong,
+                skipped,
+                entry
+                    .seed
+                    .map(|seed| seed.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                entry.regex
+            )?;
+            for error in &entry.errors {
+                writeln!(f, "{:>5}  {}", "", error.lines().next().unwrap_or_default())?;
+            }
+            match entry.status() {
+                EntryStatus::Skipped => {
+                    if let Some(skip) = &entry.skip {
+                        writeln!(f, "{:>5}  skipped: {}", "", skip.reason)?;
+                    }
+                }
+                EntryStatus::ExpectedFailure => {
+                    if let Some(expected_failure) = &entry.expected_failure {
+                        writeln!(
+                            f,
+                            "{:>5}  expected failure: {}",
+                            "", expected_failure.reason
+                        )?;
+                    }
+                }
+                EntryStatus::UnexpectedPass => {
+                    let unexpected_passes = entry
+                        .test_result
+                        .as_ref()
+                        .map(TestResult::unexpected_passes)
+                        .unwrap_or_default();
+                    if unexpected_passes.is_empty() {
+                        writeln!(
+                            f,
+                            "{:>5}  the entry passed, its expected_failure can be removed",
+                            ""
+                        )?;
+                    } else {
+                        writeln!(
+                            f,
+                            "{:>5}  these samples passed and can be removed from the expected_failure: {:?}",
+                            "", unexpected_passes
+                        )?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        write!(
+            f,
+            "{} passed, {} failed, {} errors, {} xfailed, {} xpassed, {} skipped",
+            self.count(EntryStatus::Passed),
+            self.count(EntryStatus::Failed),
+            self.count(EntryStatus::Error),
+            self.count(EntryStatus::ExpectedFailure),
+            self.count(EntryStatus::UnexpectedPass),
+            self.count(EntryStatus::Skipped)
+        )
+    }
+}
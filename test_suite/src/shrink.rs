@@ -0,0 +1,140 @@
+use itertools::Itertools;
+
+use crate::{bytes::ByteString, constants};
+
+/// Shrinks a failing input with delta debugging. In each round, the candidates of the current
+/// input (see [`shrink_candidates`]) that keep its expected outcome, as decided by
+/// `keeps_outcome`, are given to `first_failing`, which tests them and returns the position of
+/// the first one that still fails in the same way. That candidate, which is the most reduced
+/// one, becomes the current input. Returns the smallest input found.
+pub fn shrink_sample(
+    input: &ByteString,
+    keeps_outcome: impl Fn(&ByteString) -> bool,
+    mut first_failing: impl FnMut(&[ByteString]) -> anyhow::Result<Option<usize>>,
+) -> anyhow::Result<ByteString> {
+    let mut current = input.clone();
+    for _ in 0..constants::DEFAULT_SHRINK_ROUNDS {
+        let candidates: Vec<ByteString> = shrink_candidates(&current)
+            .into_iter()
+            .filter(&keeps_outcome)
+            .take(constants::DEFAULT_SHRINK_CANDIDATES)
+            .collect();
+        if candidates.is_empty() {
+            break;
+        }
+        match first_failing(&candidates)? {
+            Some(position) => current = candidates[position].clone(),
+            None => break,
+        }
+    }
+    Ok(current)
+}
+
+/// Returns smaller or simpler variations of the input to look for a minimal counterexample,
+/// the most reduced ones first:
+/// - the input without a chunk of characters, for chunks of half the input, a quarter of the
+///   input, ... down to single characters. The bytes that are not valid UTF-8 count as single
+///   characters.
+/// - the input with a character replaced by the simplest character of its kind (`0` for
+///   digits, `a` for lowercase letters and `A` for uppercase letters).
+pub fn shrink_candidates(input: &ByteString) -> Vec<ByteString> {
+    let chars = input.chars();
+    let mut candidates: Vec<ByteString> = Vec::new();
+
+    let mut chunk_size = chars.len() / 2;
+    while chunk_size > 0 {
+        for start in (0..chars.len()).step_by(chunk_size) {
+            let end = (start + chunk_size).min(chars.len());
+            candidates.push([&chars[..start], &chars[end..]].concat().concat().into());
+        }
+        chunk_size /= 2;
+    }
+
+    for (position, c) in chars.iter().enumerate() {
+        let simplest: &[u8] = match c {
+            [b'0'..=b'9'] => b"0",
+            [b'a'..=b'z'] => b"a",
+            [b'A'..=b'Z'] => b"A",
+            _ => continue,
+        };
+        if *c != simplest {
+            let mut simplified = chars.clone();
+            simplified[position] = simplest;
+            candidates.push(simplified.concat().into());
+        }
+    }
+
+    candidates
+        .into_iter()
+        .filter(|candidate| !candidate.is_empty() && candidate != input)
+        .unique()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_the_largest_chunks_first() {
+        let candidates = shrink_candidates(&"abcd".into());
+        assert_eq!(candidates[..2], ["cd".into(), "ab".into()]);
+    }
+
+    #[test]
+    fn shrinks_a_failing_input_to_a_minimal_one() {
+        // A circuit that accepts by mistake the inputs with `@` followed by a digit
+        let fails = |input: &ByteString| {
+            input
+                .windows(2)
+                .any(|pair| pair[0] == b'@' && pair[1].is_ascii_digit())
+        };
+        let first_failing = |candidates: &[ByteString]| Ok(candidates.iter().position(&fails));
+        let minimal_input =
+            shrink_sample(&"From: john@7example.com".into(), |_| true, first_failing).unwrap();
+        assert_eq!(minimal_input, "@0".into());
+    }
+
+    #[test]
+    fn only_tests_the_candidates_that_keep_the_expected_outcome() {
+        // The oracle rejects the inputs without a `.`
+        let keeps_outcome = |input: &ByteString| input.contains(&b'.');
+        let mut tested = Vec::new();
+        let minimal_input = shrink_sample(&"ab.cd".into(), keeps_outcome, |candidates| {
+            tested.extend_from_slice(candidates);
+            Ok(Some(0))
+        })
+        .unwrap();
+        assert_eq!(minimal_input, ".".into());
+        assert!(tested.iter().all(keeps_outcome));
+    }
+
+    #[test]
+    fn stops_when_no_candidate_fails() {
+        let mut rounds = 0;
+        let minimal_input = shrink_sample(
+            &"abc".into(),
+            |_| true,
+            |_| {
+                rounds += 1;
+                Ok(None)
+            },
+        )
+        .unwrap();
+        assert_eq!(minimal_input, "abc".into());
+        assert_eq!(rounds, 1);
+    }
+
+    #[test]
+    fn keeps_the_invalid_utf8_bytes_as_single_characters() {
+        let candidates = shrink_candidates(&vec![b'a', 0xff, b'b'].into());
+        assert!(candidates.contains(&vec![0xff, b'b'].into()));
+        assert!(candidates.contains(&vec![b'a', 0xff].into()));
+        assert!(candidates.contains(&vec![b'a', 0xff, b'a'].into()));
+    }
+
+    #[test]
+    fn has_no_candidates_for_a_minimal_input() {
+        assert!(shrink_candidates(&"a".into()).is_empty());
+    }
+}
@@ -1,17 +1,33 @@
 use std::{
+    collections::HashMap,
     fmt::{self, Display},
-    path::Path,
+    io::{self, Read},
     process::Command,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context};
-use rand::{self, prelude::Distribution};
+use itertools::Itertools;
+use rand::{self, prelude::Distribution, rngs::StdRng, SeedableRng};
 use rand_regex::Regex;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    code::Code,
+    bytes::ByteString,
+    code::{test_fn_name, Code, NoirTest},
     constants,
-    db::{DbEntry, InputWithSubstrs, RegexFragment, RegexInput, SamplesPass},
+    db::{DbEntry, InputWithSubstrs, RawRegex, RegexFragment, RegexInput, SamplesPass},
+    dfa::Dfa,
+    mutation::Mutator,
+    oracle::{MatchMode, Oracle},
+    padding::{
+        padded_test, variant_test, zero_padded, PaddingDifference, PaddingReport, PaddingVariant,
+        PADDING_VARIANTS,
+    },
+    shrink::shrink_sample,
+    transitions::{self, CaptureSampler},
+    workspace::Workspace,
 };
 use std::fmt::Write;
 
@@ -23,7 +39,8 @@ pub enum Error {
     TestFailed(TestResult),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TestResult {
     Standard(StandardTestResult),
     Substring(SubstringTestResult),
@@ -36,6 +53,174 @@ impl TestResult {
             TestResult::Substring(result) => result.passed(),
         }
     }
+
+    /// Number of samples that were judged correctly.
+    pub fn successful_count(&self) -> usize {
+        match self {
+            TestResult::Standard(result) => result.successful_tests.len(),
+            TestResult::Substring(result) => result.standard_test_result.successful_tests.len(),
+        }
+    }
+
+    /// Returns the samples that were judged correctly.
+    pub fn successful_tests(&self) -> &[ByteString] {
+        match self {
+            TestResult::Standard(result) => &result.successful_tests,
+            TestResult::Substring(result) => &result.standard_test_result.successful_tests,
+        }
+    }
+
+    /// Returns the samples that were judged incorrectly together with the category of the
+    /// failure, so they can be updated.
+    fn failed_tests_mut(&mut self) -> Vec<(FailureCategory, &mut FailedSample)> {
+        let (standard_test_result, incorrect_substring_tests) = match self {
+            TestResult::Standard(result) => (result, &mut [][..]),
+            TestResult::Substring(result) => (
+                &mut result.standard_test_result,
+                &mut result.incorrect_substring_tests[..],
+            ),
+        };
+        standard_test_result
+            .false_positives
+            .iter_mut()
+            .map(|sample| (FailureCategory::FalsePositive, sample))
+            .chain(
+                standard_test_result
+                    .false_negatives
+                    .iter_mut()
+                    .map(|sample| (FailureCategory::FalseNegative, sample)),
+            )
+            .chain(
+                incorrect_substring_tests
+                    .iter_mut()
+                    .map(|sample| (FailureCategory::IncorrectSubstrings, sample)),
+            )
+            .collect()
+    }
+
+    /// Returns the samples that were judged incorrectly together with the category of
+    /// the failure.
+    pub fn failed_tests(&self) -> Vec<(FailureCategory, &FailedSample)> {
+        let (standard_test_result, incorrect_substring_tests) = match self {
+            TestResult::Standard(result) => (result, &[][..]),
+            TestResult::Substring(result) => (
+                &result.standard_test_result,
+                &result.incorrect_substring_tests[..],
+            ),
+        };
+        let false_positives = standard_test_result
+            .false_positives
+            .iter()
+            .map(|sample| (FailureCategory::FalsePositive, sample));
+        let false_negatives = standard_test_result
+            .false_negatives
+            .iter()
+            .map(|sample| (FailureCategory::FalseNegative, sample));
+        let incorrect_substrings = incorrect_substring_tests
+            .iter()
+            .map(|sample| (FailureCategory::IncorrectSubstrings, sample));
+        false_positives
+            .chain(false_negatives)
+            .chain(incorrect_substrings)
+            .collect()
+    }
+
+    /// Whether no sample was tested or skipped.
+    fn is_empty(&self) -> bool {
+        self.successful_count() == 0
+            && self.failed_tests().is_empty()
+            && self.skipped_tests().is_empty()
+    }
+
+    /// Adds the samples of another result of the same kind to this one.
+    fn extend(&mut self, other: TestResult) {
+        match (self, other) {
+            (TestResult::Standard(result), TestResult::Standard(other)) => result.extend(other),
+            (TestResult::Substring(result), TestResult::Substring(other)) => {
+                result
+                    .standard_test_result
+                    .extend(other.standard_test_result);
+                result
+                    .incorrect_substring_tests
+                    .extend(other.incorrect_substring_tests);
+            }
+            _ => unreachable!("the results of an entry are always of the same kind"),
+        }
+    }
+
+    /// Returns the random samples that were not tested.
+    pub fn skipped_tests(&self) -> &[SkippedSample] {
+        match self {
+            TestResult::Standard(result) => &result.skipped_tests,
+            TestResult::Substring(result) => &result.standard_test_result.skipped_tests,
+        }
+    }
+
+    /// Returns the samples of the expected failure of the entry that were judged incorrectly.
+    pub fn expected_failures(&self) -> &[FailedSample] {
+        match self {
+            TestResult::Standard(result) => &result.expected_failures,
+            TestResult::Substring(result) => &result.standard_test_result.expected_failures,
+        }
+    }
+
+    /// Returns the samples of the expected failure of the entry that were judged correctly.
+    pub fn unexpected_passes(&self) -> &[ByteString] {
+        match self {
+            TestResult::Standard(result) => &result.unexpected_passes,
+            TestResult::Substring(result) => &result.standard_test_result.unexpected_passes,
+        }
+    }
+
+    /// Moves the given samples out of the failed and the successful samples: the ones judged
+    /// incorrectly are expected failures and the ones judged correctly are unexpected passes.
+    fn mark_expected_failures(&mut self, samples: &[ByteString]) {
+        let (standard_test_result, incorrect_substring_tests) = match self {
+            TestResult::Standard(result) => (result, None),
+            TestResult::Substring(result) => (
+                &mut result.standard_test_result,
+                Some(&mut result.incorrect_substring_tests),
+            ),
+        };
+        let StandardTestResult {
+            successful_tests,
+            false_positives,
+            false_negatives,
+            expected_failures,
+            unexpected_passes,
+            ..
+        } = standard_test_result;
+        for failed_tests in [false_positives, false_negatives]
+            .into_iter()
+            .chain(incorrect_substring_tests)
+        {
+            let (expected, unexpected) = std::mem::take(failed_tests)
+                .into_iter()
+                .partition(|sample| samples.contains(&sample.input));
+            *failed_tests = unexpected;
+            expected_failures.extend::<Vec<_>>(expected);
+        }
+        let (passes, successes): (Vec<_>, _) = std::mem::take(successful_tests)
+            .into_iter()
+            .partition(|input| samples.contains(input));
+        *successful_tests = successes;
+        // A sample can also be generated as a random sample or a mutant
+        for input in passes {
+            if !unexpected_passes.contains(&input) {
+                unexpected_passes.push(input);
+            }
+        }
+    }
+
+    /// Number of samples that were judged incorrectly.
+    pub fn failed_count(&self) -> usize {
+        match self {
+            TestResult::Standard(result) => result.failed_count(),
+            TestResult::Substring(result) => {
+                result.standard_test_result.failed_count() + result.incorrect_substring_tests.len()
+            }
+        }
+    }
 }
 
 impl fmt::Display for TestResult {
@@ -47,37 +232,243 @@ impl fmt::Display for TestResult {
     }
 }
 
+/// Category of a sample that was judged incorrectly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureCategory {
+    /// Input should have been rejected, but was accepted.
+    FalsePositive,
+    /// Input should have been accepted, but was rejected.
+    FalseNegative,
+    /// Input with substrings that didn't pass the test.
+    IncorrectSubstrings,
+}
+
+impl Display for FailureCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureCategory::FalsePositive => write!(f, "false positive"),
+            FailureCategory::FalseNegative => write!(f, "false negative"),
+            FailureCategory::IncorrectSubstrings => write!(f, "incorrect substrings"),
+        }
+    }
+}
+
+/// Cause of a Noir test not having the expected outcome, deduced from the `nargo test` output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureCause {
+    /// The Noir project didn't compile (e.g. an array size error).
+    CompileError,
+    /// The regex match failed inside the circuit.
+    ConstraintFailure,
+    /// The number of extracted substrings is different from the expected one.
+    WrongSubstringCount,
+    /// An extracted substring has a different byte than the expected one.
+    WrongSubstringByte,
+    /// An extracted substring has a different length than the expected one.
+    WrongSubstringLength,
+    /// The test was expected to fail, but the circuit accepted the input.
+    UnexpectedPass,
+    /// `nargo test` didn't finish within the time limit.
+    Timeout,
+    /// The failure could not be classified (e.g. a crash of nargo).
+    Unknown,
+}
+
+impl Display for FailureCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureCause::CompileError => write!(f, "compile error"),
+            FailureCause::ConstraintFailure => write!(f, "constraint failure"),
+            FailureCause::WrongSubstringCount => write!(f, "wrong substring count"),
+            FailureCause::WrongSubstringByte => write!(f, "wrong substring byte"),
+            FailureCause::WrongSubstringLength => write!(f, "wrong substring length"),
+            FailureCause::UnexpectedPass => write!(f, "unexpected pass"),
+            FailureCause::Timeout => write!(f, "timeout"),
+            FailureCause::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+impl FailureCause {
+    /// Classifies the failure of a test from its output. The failed assertion is identified
+    /// by the source line that nargo prints below the error.
+    fn classify(test: &NoirTest, outcome: &NargoTestOutcome) -> Self {
+        if let Some(cause) = outcome.failure_cause {
+            return cause;
+        }
+        let output = &outcome.output;
+        match test {
+            NoirTest::Standard {
+                should_fail: true, ..
+            } => FailureCause::UnexpectedPass,
+            _ if output.contains("assert(capture_sequences.len() ==") => {
+                FailureCause::WrongSubstringCount
+            }
+            _ if output.contains(".get(") && output.contains("assert(substr") => {
+                FailureCause::WrongSubstringByte
+            }
+            _ if output.contains(".len() ==") && output.contains("assert(substr") => {
+                FailureCause::WrongSubstringLength
+            }
+            _ if [
+                "Failed constraint",
+                "Failed assertion",
+                "Match not found",
+                "no match",
+            ]
+            .iter()
+            .any(|message| output.contains(message)) =>
+            {
+                FailureCause::ConstraintFailure
+            }
+            _ => FailureCause::Unknown,
+        }
+    }
+}
+
+/// A sample for which the Noir test didn't have the expected outcome.
+#[derive(Debug, Serialize)]
+pub struct FailedSample {
+    /// The input bytes.
+    pub input: ByteString,
+    /// Classification of the failure.
+    pub cause: FailureCause,
+    /// Console output of `nargo test` for the test of this sample.
+    pub nargo_output: String,
+    /// Smallest input found by shrinking the sample that fails in the same way, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimal_input: Option<ByteString>,
+    /// How the sample was obtained.
+    pub origin: SampleOrigin,
+    /// Substrings that the test expected to be extracted, for the tests with substrings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_substrings: Option<Vec<ByteString>>,
+}
+
+impl FailedSample {
+    fn new(test: &NoirTest, outcome: NargoTestOutcome, origin: SampleOrigin) -> Self {
+        Self {
+            input: test.input().clone(),
+            cause: FailureCause::classify(test, &outcome),
+            nargo_output: outcome.output,
+            minimal_input: None,
+            origin,
+            expected_substrings: match test {
+                NoirTest::Substring(sample) => Some(sample.expected_substrings.clone()),
+                NoirTest::Standard { .. } => None,
+            },
+        }
+    }
+}
+
+/// How a tested sample was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SampleOrigin {
+    /// A sample given in the entry.
+    Given,
+    /// A random sample generated from the regex.
+    Random,
+    /// A mutant of a passing sample.
+    Mutant,
+    /// An input built to take a transition of the automaton that the other samples don't take.
+    Transition,
+}
+
+/// Tests that are run together in a single `nargo test` run, with the origin of each one.
+#[derive(Default)]
+struct TestBatch {
+    tests: Vec<NoirTest>,
+    origins: Vec<SampleOrigin>,
+}
+
+impl TestBatch {
+    /// Adds tests that have the same origin.
+    fn add(&mut self, tests: impl IntoIterator<Item = NoirTest>, origin: SampleOrigin) {
+        let previous_len = self.tests.len();
+        self.tests.extend(tests);
+        self.origins
+            .resize(self.origins.len() + self.tests.len() - previous_len, origin);
+    }
+
+    /// Labels the tests by the inputs padded with zeros up to the input size that the circuit
+    /// receives, and leaves out the ones that are not run padded (see [`padded_test`]).
+    fn pad(&mut self, input_size: usize, oracle: &Oracle) {
+        let tests = std::mem::take(&mut self.tests);
+        let origins = std::mem::take(&mut self.origins);
+        for (test, origin) in tests.into_iter().zip(origins) {
+            if let Some(test) = padded_test(test, input_size, oracle) {
+                self.tests.push(test);
+                self.origins.push(origin);
+            }
+        }
+    }
+}
+
+/// A random sample that was not tested, together with the reason why.
+#[derive(Debug, Serialize)]
+pub struct SkippedSample {
+    /// The input bytes.
+    pub input: ByteString,
+    /// Why the sample was not tested.
+    pub reason: String,
+}
+
 /// Result report for a standard test.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct StandardTestResult {
     /// All inputs that were correctly accepted or correctly rejected
-    successful_tests: Vec<String>,
+    successful_tests: Vec<ByteString>,
     /// Input should have been rejected, but was accepted
-    false_positives: Vec<String>,
+    false_positives: Vec<FailedSample>,
     /// Input should have been accepted, but was rejected
-    false_negatives: Vec<String>,
+    false_negatives: Vec<FailedSample>,
+    /// Random samples that were generated but not tested
+    skipped_tests: Vec<SkippedSample>,
+    /// Samples of the expected failure of the entry that were judged incorrectly
+    expected_failures: Vec<FailedSample>,
+    /// Samples of the expected failure of the entry that were judged correctly
+    unexpected_passes: Vec<ByteString>,
 }
 
 /// Result report for a test with substring generation.
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize)]
 pub struct SubstringTestResult {
     standard_test_result: StandardTestResult,
     /// Tests with substrings, but failed.
     /// These are the cases that should be rechecked manually
-    incorrect_substring_tests: Vec<String>,
+    incorrect_substring_tests: Vec<FailedSample>,
+}
+
+/// Returns the inputs of the failed samples together with the cause of the failure.
+fn inputs(samples: &[FailedSample]) -> Vec<String> {
+    samples
+        .iter()
+        .map(|sample| match &sample.minimal_input {
+            Some(minimal_input) => format!(
+                "{:?} ({}, minimal input {:?})",
+                sample.input, sample.cause, minimal_input
+            ),
+            None => format!("{:?} ({})", sample.input, sample.cause),
+        })
+        .collect()
 }
 
 impl StandardTestResult {
     /// Creates a new test result.
     pub fn new(
-        successful_tests: Vec<String>,
-        false_positives: Vec<String>,
-        false_negatives: Vec<String>,
+        successful_tests: Vec<ByteString>,
+        false_positives: Vec<FailedSample>,
+        false_negatives: Vec<FailedSample>,
+        skipped_tests: Vec<SkippedSample>,
     ) -> Self {
         Self {
             successful_tests,
             false_positives,
             false_negatives,
+            skipped_tests,
+            ..Default::default()
         }
     }
 
@@ -86,6 +477,21 @@ impl StandardTestResult {
     pub fn passed(&self) -> bool {
         self.false_positives.is_empty() && self.false_negatives.is_empty()
     }
+
+    /// Adds the samples of another result to this one.
+    fn extend(&mut self, other: StandardTestResult) {
+        self.successful_tests.extend(other.successful_tests);
+        self.false_positives.extend(other.false_positives);
+        self.false_negatives.extend(other.false_negatives);
+        self.skipped_tests.extend(other.skipped_tests);
+        self.expected_failures.extend(other.expected_failures);
+        self.unexpected_passes.extend(other.unexpected_passes);
+    }
+
+    /// Number of false positives and false negatives.
+    fn failed_count(&self) -> usize {
+        self.false_positives.len() + self.false_negatives.len()
+    }
 }
 
 impl Display for StandardTestResult {
@@ -100,13 +506,34 @@ impl Display for StandardTestResult {
         if !self.false_negatives.is_empty() {
             output.push_str(&format!(
                 "The following samples that should match the regex did NOT pass the Noir test:\n{:?}\n",
-                self.false_negatives
+                inputs(&self.false_negatives)
             ));
         }
         if !self.false_positives.is_empty() {
             output.push_str(&format!(
                 "The following samples that should NOT match the regex DID pass the Noir test:\n{:?}\n",
-                self.false_positives
+                inputs(&self.false_positives)
+            ));
+        }
+        if !self.skipped_tests.is_empty() {
+            output.push_str(&format!(
+                "The following random samples were skipped:\n{:?}\n",
+                self.skipped_tests
+                    .iter()
+                    .map(|sample| format!("{:?} ({})", sample.input, sample.reason))
+                    .collect::<Vec<_>>()
+            ));
+        }
+        if !self.expected_failures.is_empty() {
+            output.push_str(&format!(
+                "The following samples failed as expected:\n{:?}\n",
+                inputs(&self.expected_failures)
+            ));
+        }
+        if !self.unexpected_passes.is_empty() {
+            output.push_str(&format!(
+                "The following samples were expected to fail, but were judged correctly:\n{:?}\n",
+                self.unexpected_passes
             ));
         }
         write!(f, "{}", output)
@@ -120,7 +547,7 @@ impl Display for SubstringTestResult {
         if !self.incorrect_substring_tests.is_empty() {
             output.push_str(&format!(
                 "These samples did not pass the test:\n{:?}\n",
-                self.incorrect_substring_tests
+                inputs(&self.incorrect_substring_tests)
             ));
         }
         write!(f, "{}", output)
@@ -129,15 +556,17 @@ impl Display for SubstringTestResult {
 
 impl SubstringTestResult {
     pub fn new(
-        successful_tests: Vec<String>,
-        false_positives: Vec<String>,
-        incorrect_substring_tests: Vec<String>,
+        successful_tests: Vec<ByteString>,
+        false_positives: Vec<FailedSample>,
+        incorrect_substring_tests: Vec<FailedSample>,
+        skipped_tests: Vec<SkippedSample>,
     ) -> Self {
         Self {
             standard_test_result: StandardTestResult::new(
                 successful_tests,
                 false_positives,
                 Vec::new(), // These results will be included in incorrect_substring_tests
+                skipped_tests,
             ),
             incorrect_substring_tests,
         }
@@ -149,231 +578,682 @@ impl SubstringTestResult {
     }
 }
 
+/// Options of the testing of an entry.
+pub struct TestOptions {
+    /// Seed of the random samples.
+    pub seed: u64,
+    /// Whether the failing samples are shrunk to minimal counterexamples.
+    pub shrink: bool,
+    /// Number of random samples, which overrides the number given in the entry.
+    pub random_samples: Option<usize>,
+    /// Time during which rounds of random samples keep being generated and tested.
+    pub random_budget: Option<Duration>,
+}
+
 /// Tests a given regex:
 /// - against randomly generate samples. Checks that they give the same outcome for Noir as for a Rust regex lib
-///     (the random samples are assumed to pass in both).
-///     Additionally, for the substrs case, the correctness of the substring output is also checked.
+///   (the random samples are assumed to pass in both).
+///   Additionally, for the substrs case, the correctness of the substring output is also checked.
 /// - against (user) input samples, of both passing and failing inputs.
 ///
-/// Note: raw + gen_substrs case does *not* get tested with randomly generated samples, because these are too difficult to generate.
-pub fn test_regex(regex_input: &DbEntry, code: &mut Code) -> anyhow::Result<TestResult> {
+/// All the samples of the entry are written as separate test functions into a single Noir file,
+/// so the circuit is compiled and tested with a single `nargo test` run.
+///
+/// Note: raw + gen_substrs entries are only tested with randomly generated samples when the
+/// substrings are given by the capture groups of the regex instead of explicit transitions.
+pub fn test_regex(
+    regex_input: &DbEntry,
+    code: &mut Code,
+    workspace: &Workspace,
+    options: &TestOptions,
+) -> anyhow::Result<TestResult> {
+    let start = Instant::now();
+    // All the random samples of the entry come from this generator, so the same seed gives the
+    // same samples
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let sample_number = options
+        .random_samples
+        .or(regex_input.random_samples)
+        .unwrap_or(constants::DEFAULT_SAMPLE_NUMBER);
+    let mut test_result =
+        test_samples(regex_input, code, workspace, &mut rng, sample_number, true)?;
+
+    // With a time budget, new rounds of random samples and mutants are tested until it runs out
+    if let Some(budget) = options.random_budget {
+        let mut rounds = 1;
+        while start.elapsed() < budget {
+            let round_result =
+                test_samples(regex_input, code, workspace, &mut rng, sample_number, false)?;
+            if round_result.is_empty() {
+                break;
+            }
+            test_result.extend(round_result);
+            rounds += 1;
+        }
+        log::info!(
+            "tested {} rounds of random samples in {:?}",
+            rounds,
+            start.elapsed()
+        );
+    }
+
+    if let Some(expected_failure) = &regex_input.expected_failure {
+        test_result.mark_expected_failures(&expected_failure.samples);
+    }
+
+    if !test_result.passed() {
+        if options.shrink {
+            shrink_failed_samples(&mut test_result, regex_input, code, workspace)?;
+        }
+        bail!(Error::TestFailed(test_result));
+    }
+    Ok(test_result)
+}
+
+/// Tests a round of `sample_number` random samples and the mutants of the passing samples. The
+/// samples of the entry and the inputs aimed at the transitions that the other samples don't
+/// take are only tested in the first round. When the inputs are padded up to the input size,
+/// the tests are labeled by the padded inputs.
+fn test_samples(
+    regex_input: &DbEntry,
+    code: &Code,
+    workspace: &Workspace,
+    rng: &mut StdRng,
+    sample_number: usize,
+    first_round: bool,
+) -> anyhow::Result<TestResult> {
+    let oracle = Oracle::new(&regex_input.regex.complete_regex(), regex_input.match_mode)?;
     let test_result = match &regex_input.samples_pass {
         SamplesPass::WithSubstrs(samples) => {
-            // Random sample testing for substrings is only done for decomposed setting
-            let (random_samples_correct, incorrect_substring_random_test) = match &regex_input.regex
-            {
-                // DISABLED FUZZING FOR NOW
-                // RegexInput::Decomposed(parts) => {
-                //     test_random_samples_gen_substrs(parts, regex_input.input_size as u32, code)?
-                // }
-                _ => (Vec::new(), Vec::new()),
+            // Random sample testing for substrings is done when the expected substrings can be
+            // known from the regex: the public fragments or the capture groups
+            let (random_tests, skipped_tests) = match &regex_input.regex {
+                RegexInput::Decomposed(parts) => test_random_samples_gen_substrs(
+                    parts,
+                    regex_input.input_size,
+                    regex_input.match_mode,
+                    sample_number,
+                    rng,
+                )?,
+                RegexInput::Raw(RawRegex::Simple(regex)) => test_random_samples_capture_groups(
+                    regex,
+                    regex_input.input_size,
+                    regex_input.match_mode,
+                    sample_number,
+                    rng,
+                )?,
+                RegexInput::Raw(RawRegex::WithTransitions { .. }) => (Vec::new(), Vec::new()),
             };
 
-            // Run tests for input samples. The test extracts substrings and compares them to the input for passing samples
+            // Tests for input samples. The test extracts substrings and compares them to the input for passing samples
             // For failing samples it does a standard test (no substring extraction)
-            let (input_samples_correct, incorrect_substring_given_samples_test, false_positives) =
-                test_given_samples_gensubstr(code, samples, &regex_input.samples_fail)?;
+            let given_tests = given_samples_gensubstr(samples, &regex_input.samples_fail);
+
+            // Mutants of the passing samples that shouldn't match the regex. The mutants that
+            // match are not tested, since their expected substrings are unknown
+            let passing_samples = random_tests
+                .iter()
+                .chain(&given_tests)
+                .filter_map(|test| match test {
+                    NoirTest::Substring(sample) => Some(sample.input.clone()),
+                    NoirTest::Standard { .. } => None,
+                })
+                .collect::<Vec<_>>();
+            let failing_mutants = test_for_mutants(regex_input, &passing_samples, rng)?
+                .into_iter()
+                .filter(|test| {
+                    matches!(
+                        test,
+                        NoirTest::Standard {
+                            should_fail: true,
+                            ..
+                        }
+                    )
+                })
+                .collect();
+            let mut batch = TestBatch::default();
+            batch.add(random_tests, SampleOrigin::Random);
+            if first_round {
+                batch.add(given_tests, SampleOrigin::Given);
+            }
+            batch.add(
+                select_tests_with_simulation(code, failing_mutants, sample_number),
+                SampleOrigin::Mutant,
+            );
+            if first_round {
+                // As with the mutants, only the inputs that shouldn't match are tested
+                let failing_inputs =
+                    test_for_uncovered_transitions(regex_input, code, &batch.tests)?
+                        .into_iter()
+                        .filter(|test| {
+                            matches!(
+                                test,
+                                NoirTest::Standard {
+                                    should_fail: true,
+                                    ..
+                                }
+                            )
+                        });
+                batch.add(failing_inputs, SampleOrigin::Transition);
+            }
+
+            if regex_input.pad_test_inputs {
+                batch.pad(regex_input.input_size, &oracle);
+            }
+            let outcomes = run_test_batch(code, &batch.tests, workspace)?;
 
             // Collect results
-            let mut successful_tests = random_samples_correct;
-            successful_tests.extend(input_samples_correct);
-            let mut all_incorrect_substring_tests = incorrect_substring_random_test;
-            all_incorrect_substring_tests.extend(incorrect_substring_given_samples_test);
+            let mut successful_tests = Vec::new();
+            let mut false_positives = Vec::new();
+            let mut incorrect_substring_tests = Vec::new();
+            for ((test, origin), outcome) in batch.tests.iter().zip(batch.origins).zip(outcomes) {
+                match test {
+                    _ if outcome.passed => successful_tests.push(test.input().clone()),
+                    // Not passing test can be because of incorrect regex match or incorrect substrings
+                    // The cause of the failure is classified from the nargo output
+                    NoirTest::Substring(_) => {
+                        incorrect_substring_tests.push(FailedSample::new(test, outcome, origin))
+                    }
+                    NoirTest::Standard { .. } => {
+                        false_positives.push(FailedSample::new(test, outcome, origin))
+                    }
+                }
+            }
             TestResult::Substring(SubstringTestResult::new(
                 successful_tests,
                 false_positives,
-                all_incorrect_substring_tests,
+                incorrect_substring_tests,
+                skipped_tests,
             ))
         }
         SamplesPass::WithoutSubstrs(samples_pass) => {
             // Test randomly generated samples: (probably) only passes are tested here
             // The result of a rust regex library is used to definitely decide whether it should be a pass or not
             // The Noir test is adjusted accordingly (if Rust says it should fail, the test fails and vice versa)
-            let (random_samples_correct, random_samples_false_negatives) =
-                test_for_random_samples(regex_input, code)?;
+            let random_tests = test_for_random_samples(regex_input, code, sample_number, rng)?;
 
             // Test input samples
-            let (input_samples_correct, false_positives, input_samples_false_negatives) =
-                test_given_samples_standard(code, samples_pass, &regex_input.samples_fail)?;
+            let given_tests = given_samples_standard(samples_pass, &regex_input.samples_fail);
+
+            // Mutants of the passing samples, which are likely to be rejected by the regex
+            let passing_samples = random_tests
+                .iter()
+                .chain(&given_tests)
+                .filter_map(|test| match test {
+                    NoirTest::Standard {
+                        input,
+                        should_fail: false,
+                    } => Some(input.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let mutants = test_for_mutants(regex_input, &passing_samples, rng)?;
+            let mut batch = TestBatch::default();
+            batch.add(random_tests, SampleOrigin::Random);
+            if first_round {
+                batch.add(given_tests, SampleOrigin::Given);
+            }
+            batch.add(
+                select_tests_with_simulation(code, mutants, sample_number),
+                SampleOrigin::Mutant,
+            );
+            if first_round {
+                batch.add(
+                    test_for_uncovered_transitions(regex_input, code, &batch.tests)?,
+                    SampleOrigin::Transition,
+                );
+            }
+
+            if regex_input.pad_test_inputs {
+                batch.pad(regex_input.input_size, &oracle);
+            }
+            let outcomes = run_test_batch(code, &batch.tests, workspace)?;
 
             // Collect results
-            let mut successful_tests = random_samples_correct;
-            successful_tests.extend(input_samples_correct);
-            let mut false_negatives = random_samples_false_negatives;
-            false_negatives.extend(input_samples_false_negatives);
+            let mut successful_tests = Vec::new();
+            let mut false_positives = Vec::new();
+            let mut false_negatives = Vec::new();
+            for ((test, origin), outcome) in batch.tests.iter().zip(batch.origins).zip(outcomes) {
+                match test {
+                    _ if outcome.passed => successful_tests.push(test.input().clone()),
+                    NoirTest::Standard {
+                        should_fail: true, ..
+                    } => false_positives.push(FailedSample::new(test, outcome, origin)),
+                    _ => false_negatives.push(FailedSample::new(test, outcome, origin)),
+                }
+            }
 
             TestResult::Standard(StandardTestResult::new(
                 successful_tests,
                 false_positives,
                 false_negatives,
+                Vec::new(),
             ))
         }
     };
+    Ok(test_result)
+}
 
-    if !test_result.passed() {
-        bail!(Error::TestFailed(test_result));
+/// Tests the given samples of the entry together with their padding variants (length-exact,
+/// zero-padded and with trailing garbage) in a single `nargo test` run, and reports the
+/// variants whose outcome differs from the outcome of their sample. The random characters of
+/// the trailing garbage come from the seed. When the inputs of the entry are already padded
+/// with zeros, the samples are labeled by their padded inputs and the zero-padded variant,
+/// which would be the same test as its sample, is left out.
+pub fn test_padding(
+    regex_input: &DbEntry,
+    code: &Code,
+    workspace: &Workspace,
+    seed: u64,
+) -> anyhow::Result<PaddingReport> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let tests = match &regex_input.samples_pass {
+        SamplesPass::WithSubstrs(samples) => {
+            given_samples_gensubstr(samples, &regex_input.samples_fail)
+        }
+        SamplesPass::WithoutSubstrs(samples) => {
+            given_samples_standard(samples, &regex_input.samples_fail)
+        }
+    };
+    let oracle = Oracle::new(&regex_input.regex.complete_regex(), regex_input.match_mode)?;
+    let tests: Vec<NoirTest> = if regex_input.pad_test_inputs {
+        tests
+            .into_iter()
+            .filter_map(|test| padded_test(test, regex_input.input_size, &oracle))
+            .collect()
+    } else {
+        tests
+    };
+
+    let mut variants = Vec::new();
+    let mut variant_tests = Vec::new();
+    for (index, test) in tests.iter().enumerate() {
+        for variant in PADDING_VARIANTS {
+            if regex_input.pad_test_inputs && variant == PaddingVariant::ZeroPadded {
+                continue;
+            }
+            // Zero bytes before the sample change the expected outcome if the match must start
+            // at the beginning of the input
+            let variant_test = variant
+                .apply(
+                    test.input(),
+                    regex_input.input_size,
+                    oracle.is_anchored_start(),
+                    &mut rng,
+                )
+                .and_then(|variant_input| variant_test(test, variant, variant_input, &oracle));
+            if let Some(variant_test) = variant_test {
+                variants.push((index, variant));
+                variant_tests.push(variant_test);
+            }
+        }
     }
-    Ok(test_result)
+
+    let batch: Vec<NoirTest> = tests.iter().cloned().chain(variant_tests).collect();
+    let outcomes = run_test_batch(code, &batch, workspace)?;
+    let (sample_outcomes, variant_outcomes) = outcomes.split_at(tests.len());
+
+    let mut report = PaddingReport {
+        tested: variants.len(),
+        differences: Vec::new(),
+    };
+    for ((index, variant), (variant_test, outcome)) in variants
+        .into_iter()
+        .zip(batch[tests.len()..].iter().zip(variant_outcomes))
+    {
+        let sample_passed = sample_outcomes[index].passed;
+        if outcome.passed != sample_passed {
+            report.differences.push(PaddingDifference {
+                input: tests[index].input().clone(),
+                variant,
+                variant_input: variant_test.input().clone(),
+                sample_passed,
+                nargo_output: outcome.output.clone(),
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Shrinks the failing samples to minimal counterexamples and suggests them as regression
+/// cases. Only the samples whose expected outcome agrees with the oracle are shrunk, and the
+/// samples with incorrect substrings are only shrunk when the circuit rejects them, since the
+/// expected substrings of the smaller inputs are unknown.
+fn shrink_failed_samples(
+    test_result: &mut TestResult,
+    regex_input: &DbEntry,
+    code: &Code,
+    workspace: &Workspace,
+) -> anyhow::Result<()> {
+    let oracle = Oracle::new(&regex_input.regex.complete_regex(), regex_input.match_mode)?;
+    for (category, sample) in test_result.failed_tests_mut() {
+        let should_fail = match category {
+            FailureCategory::FalsePositive => true,
+            FailureCategory::FalseNegative => false,
+            FailureCategory::IncorrectSubstrings
+                if sample.cause == FailureCause::ConstraintFailure =>
+            {
+                false
+            }
+            FailureCategory::IncorrectSubstrings => continue,
+        };
+        // The oracle decides on the input that the circuit receives
+        let accepts = |input: &[u8]| {
+            if regex_input.pad_test_inputs {
+                oracle.accepts(&zero_padded(input, regex_input.input_size))
+            } else {
+                oracle.accepts(input)
+            }
+        };
+        if accepts(&sample.input) == should_fail {
+            continue;
+        }
+
+        // The candidates of each round are tested in a single `nargo test` run, and they
+        // have to fail with the same cause
+        let minimal_input = shrink_sample(
+            &sample.input,
+            |candidate| accepts(candidate) != should_fail,
+            |candidates| {
+                let tests: Vec<NoirTest> = candidates
+                    .iter()
+                    .map(|input| NoirTest::Standard {
+                        input: input.clone(),
+                        should_fail,
+                    })
+                    .collect();
+                let outcomes = run_test_batch(code, &tests, workspace)?;
+                Ok(tests.iter().zip(&outcomes).position(|(test, outcome)| {
+                    !outcome.passed && FailureCause::classify(test, outcome) == sample.cause
+                }))
+            },
+        )?;
+        if minimal_input != sample.input {
+            log::info!(
+                "regression case: the {} {:?} was shrunk to {:?}, which can be added to {}",
+                category,
+                sample.input,
+                minimal_input,
+                if should_fail {
+                    "samples_fail"
+                } else {
+                    "samples_pass"
+                }
+            );
+            sample.minimal_input = Some(minimal_input);
+        }
+    }
+    Ok(())
 }
 
-/// Test the input samples for gensubstrs case:
+/// Creates the tests for the input samples in the gensubstrs case:
 /// - samples_pass; each sample has an input and expected substring outputs.
-///                 the input should pass the regex check & the substrings should match the expected output
+///   the input should pass the regex check & the substrings should match the expected output
 /// - samples_fail: input shouldn't pass regex check. Substrings are disregarded here
-fn test_given_samples_gensubstr(
-    code: &mut Code,
-    samples_pass: &Vec<InputWithSubstrs>,
-    samples_fail: &[String],
-) -> anyhow::Result<(Vec<String>, Vec<String>, Vec<String>)> {
-    let mut correct_samples = Vec::new();
-    let mut false_positives = Vec::new();
-    let mut incorrect_substring_tests = Vec::new();
-
+fn given_samples_gensubstr(
+    samples_pass: &[InputWithSubstrs],
+    samples_fail: &[ByteString],
+) -> Vec<NoirTest> {
     // For passing samples check:
     // - regex match passes
     // - correct amount of substrings are extracted
     // - extracted substrings are correct
-    for sample in samples_pass {
-        let test_passed = run_single_gen_substr_test(code, sample)?;
-
-        if test_passed {
-            correct_samples.push(sample.input.clone());
-        } else {
-            // Not passing test can be because of incorrect regex match or incorrect substrings
-            // Further manual testing will be needed to verify
-            incorrect_substring_tests.push(sample.input.clone());
-        }
-    }
+    let passing_tests = samples_pass.iter().cloned().map(NoirTest::Substring);
 
     // Samples fail are only checked on failing regex match;
     // No specific substrings are compared (since that doesn't make sense)
-    for failing_sample in samples_fail {
-        let correct_result = run_single_standard_test(code, failing_sample, true)?;
-
-        if correct_result {
-            correct_samples.push(failing_sample.clone());
-        } else {
-            false_positives.push(failing_sample.clone());
-        }
-    }
+    let failing_tests = samples_fail.iter().map(|sample| NoirTest::Standard {
+        input: sample.clone(),
+        should_fail: true,
+    });
 
-    Ok((correct_samples, incorrect_substring_tests, false_positives))
+    passing_tests.chain(failing_tests).collect()
 }
 
-/// Test the regex for gives samples that are expected to pass & fail respectively
+/// Creates the tests for the given samples that are expected to pass & fail respectively
 /// Note that the user input decides whether a sample is expected to pass/fail
 /// (this is *not* checked again a regex Rust impl)
-fn test_given_samples_standard(
-    code: &Code,
-    test_set_pass: &[String],
-    test_set_fail: &[String],
-) -> anyhow::Result<(Vec<String>, Vec<String>, Vec<String>)> {
-    let mut correct_samples = Vec::new();
-    let mut false_positives = Vec::new();
-    let mut false_negatives = Vec::new();
-
-    // Helper function to process each test set
-    let mut process_samples = |test_set: &[String], should_fail: bool| -> anyhow::Result<()> {
-        for string in test_set {
-            let correct_result = run_single_standard_test(code, string, should_fail)?;
-
-            if correct_result {
-                correct_samples.push(string.clone());
-            } else if should_fail {
-                false_positives.push(string.clone());
-            } else {
-                false_negatives.push(string.clone());
-            }
-        }
-        Ok(())
-    };
+fn given_samples_standard(
+    test_set_pass: &[ByteString],
+    test_set_fail: &[ByteString],
+) -> Vec<NoirTest> {
+    let passing_tests = test_set_pass.iter().map(|sample| NoirTest::Standard {
+        input: sample.clone(),
+        should_fail: false,
+    });
+    let failing_tests = test_set_fail.iter().map(|sample| NoirTest::Standard {
+        input: sample.clone(),
+        should_fail: true,
+    });
 
-    // Process passing and failing sets
-    process_samples(test_set_pass, false)?;
-    process_samples(test_set_fail, true)?;
-
-    Ok((correct_samples, false_negatives, false_positives))
+    passing_tests.chain(failing_tests).collect()
 }
 
+/// Generates random samples for a decomposed regex by sampling each fragment separately, so
+/// the expected substrings are the samples of the public fragments. A sample is only tested
+/// if the expected substrings are well defined, otherwise it is skipped with the reason:
+/// - the concatenated sample must fit in the input size and match the complete regex.
+/// - the sample must be split into the fragments in exactly one way. For example, for the
+///   fragments `[a-z]+` and `[a-z]` the sample `abc` could be split as `ab|c` or `a|bc`
+///   depending on the random choices, so the expected substrings are ambiguous.
 fn test_random_samples_gen_substrs(
-    regex_parts: &Vec<RegexFragment>,
-    max_inputsize: u32,
-    code: &mut Code,
-) -> Result<(Vec<String>, Vec<String>), anyhow::Error> {
-    let mut random_samples_correct = Vec::new();
-    let mut incorrect_substring_tests = Vec::new();
-
-    let mut rng = rand::thread_rng();
-
-    // Run DEFAULT_SAMPLE_NUMBER of tests
-    for _ in 0..constants::DEFAULT_SAMPLE_NUMBER {
-        let mut substrings = Vec::<String>::new();
-        let mut total_string = String::new();
-        let mut valid_sample = true;
-
-        for part in regex_parts {
-            // Create the regex for this part
-            let regex_part = Regex::compile(&part.regex_def, max_inputsize);
-            let sample = match regex_part {
-                Ok(regex_part) => regex_part.sample(&mut rng),
-                Err(err) => {
-                    log::info!(
-                        "ignoring the random testing - 
+    regex_parts: &[RegexFragment],
+    max_inputsize: usize,
+    match_mode: MatchMode,
+    sample_number: usize,
+    rng: &mut StdRng,
+) -> anyhow::Result<(Vec<NoirTest>, Vec<SkippedSample>)> {
+    let mut tests = Vec::new();
+    let mut skipped_tests = Vec::new();
+
+    // Each fragment gets a proportional part of the input size, so that the concatenation
+    // usually fits in the input
+    let max_repeat = (max_inputsize / regex_parts.len().max(1)).max(1) as u32;
+    let generators = match regex_parts
+        .iter()
+        .map(|part| sample_generator(&part.regex_def, max_repeat))
+        .collect::<anyhow::Result<Vec<_>>>()
+    {
+        Ok(generators) => generators,
+        Err(err) => {
+            log::info!(
+                "ignoring the random testing - 
                 the random samples were not generated due to the following error: {:?}",
-                        err
-                    );
-                    valid_sample = false;
-                    String::new()
-                }
-            };
+                err
+            );
+            return Ok((tests, skipped_tests));
+        }
+    };
+    let fragment_matchers = regex_parts
+        .iter()
+        .map(|part| regex::bytes::Regex::new(&format!("^(?:{})$", part.regex_def)))
+        .collect::<Result<Vec<_>, _>>()
+        .context("error parsing the fragments in the ground truth checker")?;
+    let complete_regex = regex_parts
+        .iter()
+        .map(|part| part.regex_def.as_str())
+        .collect::<String>();
+    let ground_truth_checker = Oracle::new(&complete_regex, match_mode)?;
 
-            // If any of the parts wasn't generated correctly we consider it an invalid sample and
-            // it will be ignored
-            if valid_sample {
-                if part.is_public {
-                    substrings.push(sample.clone());
-                }
-                // Concatenate this sample to the total_string
-                total_string.push_str(&sample);
+    // Generate sample_number of tests
+    for _ in 0..sample_number {
+        let mut substrings = Vec::<Vec<u8>>::new();
+        let mut total_string = Vec::<u8>::new();
+
+        for (part, generator) in regex_parts.iter().zip(&generators) {
+            let sample: Vec<u8> = generator.sample(rng);
+            if part.is_public {
+                substrings.push(sample.clone());
             }
+            // Concatenate this sample to the total_string
+            total_string.extend_from_slice(&sample);
         }
 
-        let input_with_substring = InputWithSubstrs {
-            input: total_string.clone(),
-            // Don't pass empty substrings
-            expected_substrings: substrings.into_iter().filter(|s| !s.is_empty()).collect(),
+        // Ignore empty samples
+        if total_string.is_empty() {
+            continue;
+        }
+
+        let skip_reason = if total_string.len() > max_inputsize {
+            Some("the sample is longer than the input size")
+        } else if !ground_truth_checker.accepts(&total_string) {
+            Some("the sample doesn't match the complete regex")
+        } else {
+            match count_fragment_splits(&total_string, &fragment_matchers) {
+                0 => Some("the sample can't be split into the fragments"),
+                1 => None,
+                _ => Some("the boundaries between the fragments are ambiguous"),
+            }
         };
 
+        match skip_reason {
+            Some(reason) => {
+                let input = ByteString::from(total_string);
+                log::info!("skipping the random sample {:?}: {}", input, reason);
+                skipped_tests.push(SkippedSample {
+                    input,
+                    reason: reason.to_string(),
+                });
+            }
+            None => tests.push(NoirTest::Substring(InputWithSubstrs {
+                input: total_string.into(),
+                // Don't pass empty substrings
+                expected_substrings: substrings
+                    .into_iter()
+                    .filter(|s| !s.is_empty())
+                    .map(ByteString::from)
+                    .collect(),
+                circom_testname: None,
+            })),
+        }
+    }
+
+    Ok((tests, skipped_tests))
+}
+
+/// Generates random samples for a raw regex whose capture groups mark the substrings. The
+/// expected substrings are the bytes of the capture groups in the sample. A sample is skipped if
+/// it is longer than the input size or it doesn't match the regex. A regex without capture
+/// groups has no random samples.
+fn test_random_samples_capture_groups(
+    regex: &str,
+    max_inputsize: usize,
+    match_mode: MatchMode,
+    sample_number: usize,
+    rng: &mut StdRng,
+) -> anyhow::Result<(Vec<NoirTest>, Vec<SkippedSample>)> {
+    let mut tests = Vec::new();
+    let mut skipped_tests = Vec::new();
+
+    let sampler = match CaptureSampler::new(regex, max_inputsize as u32) {
+        Err(err)
+            if matches!(
+                err.downcast_ref(),
+                Some(transitions::Error::NoCaptureGroups)
+            ) =>
+        {
+            log::info!(
+                "no random samples are generated for the regex {:?}: {}",
+                regex,
+                err
+            );
+            return Ok((tests, skipped_tests));
+        }
+        sampler => sampler?,
+    };
+    let ground_truth_checker = Oracle::new(regex, match_mode)?;
+
+    for _ in 0..sample_number {
+        let sample = sampler.sample(rng, max_inputsize);
+
         // Ignore empty samples
-        if !input_with_substring.input.is_empty() {
-            let test_passed = run_single_gen_substr_test(code, &input_with_substring)?;
-            if test_passed {
-                random_samples_correct.push(total_string);
-            } else {
-                incorrect_substring_tests.push(total_string);
+        if sample.input.is_empty() {
+            continue;
+        }
+
+        let skip_reason = if sample.input.len() > max_inputsize {
+            Some("the sample is longer than the input size")
+        } else if !ground_truth_checker.accepts(&sample.input) {
+            Some("the sample doesn't match the complete regex")
+        } else {
+            None
+        };
+
+        match skip_reason {
+            Some(reason) => {
+                let input = ByteString::from(sample.input);
+                log::info!("skipping the random sample {:?}: {}", input, reason);
+                skipped_tests.push(SkippedSample {
+                    input,
+                    reason: reason.to_string(),
+                });
+            }
+            None => tests.push(NoirTest::Substring(InputWithSubstrs {
+                expected_substrings: sample
+                    .expected_substrings()
+                    .into_iter()
+                    .map(ByteString::from)
+                    .collect(),
+                input: sample.input.into(),
+                circom_testname: None,
+            })),
+        }
+    }
+
+    Ok((tests, skipped_tests))
+}
+
+/// Counts in how many ways the input can be split into consecutive pieces such that each piece
+/// fully matches the corresponding fragment. The count saturates at 2, since we are only
+/// interested in whether the split is unique.
+fn count_fragment_splits(input: &[u8], fragment_matchers: &[regex::bytes::Regex]) -> usize {
+    // ways[p] is the number of ways of splitting input[..p] into the fragments processed so far
+    let mut ways = vec![0; input.len() + 1];
+    ways[0] = 1;
+    for matcher in fragment_matchers {
+        let mut next_ways = vec![0; input.len() + 1];
+        for start in (0..=input.len()).filter(|&start| ways[start] > 0) {
+            for end in start..=input.len() {
+                if matcher.is_match(&input[start..end]) {
+                    next_ways[end] = (next_ways[end] + ways[start]).min(2);
+                }
             }
         }
+        ways = next_ways;
     }
+    ways[input.len()]
+}
 
-    Ok((random_samples_correct, incorrect_substring_tests))
+/// Creates the generator of random samples for the regex. The regex is parsed without requiring
+/// UTF-8, so that the regexes on raw bytes (`(?-u:...)`) also get samples.
+fn sample_generator(regex: &str, max_repeat: u32) -> anyhow::Result<Regex> {
+    let hir = regex_syntax::ParserBuilder::new()
+        .utf8(false)
+        .build()
+        .parse(regex)
+        .context("error parsing the regex for the random samples")?;
+    Ok(Regex::with_hir(hir, max_repeat)?)
 }
 
+/// Generates random samples for the regex and selects the ones that are tested with Noir. The
+/// samples are first run through the automaton simulated in Rust: all the samples on which it
+/// disagrees with the Rust regex lib are tested, plus `sample_number` of the samples on which it
+/// agrees.
 fn test_for_random_samples(
     regex_input: &DbEntry,
-    code: &mut Code,
-) -> Result<(Vec<String>, Vec<String>), anyhow::Error> {
-    let str_generator_result = Regex::compile(
+    code: &Code,
+    sample_number: usize,
+    rng: &mut StdRng,
+) -> Result<Vec<NoirTest>, anyhow::Error> {
+    let str_generator_result = sample_generator(
         &regex_input.regex.complete_regex(),
         regex_input.input_size as u32,
     );
-    let random_samples = match str_generator_result {
-        Ok(str_generator) => {
-            let rng = rand::thread_rng();
-            str_generator
-                .sample_iter(rng)
-                .take(constants::DEFAULT_SAMPLE_NUMBER)
-                .collect::<Vec<String>>()
-        }
+    let random_samples: Vec<ByteString> = match str_generator_result {
+        Ok(str_generator) => str_generator
+            .sample_iter(rng)
+            .take(constants::DEFAULT_SIMULATED_SAMPLE_NUMBER.max(sample_number))
+            .collect::<Vec<Vec<u8>>>(),
         Err(err) => {
             log::info!(
                 "ignoring the random testing - 
@@ -385,81 +1265,354 @@ fn test_for_random_samples(
     }
     .into_iter()
     .filter(|s| !s.is_empty()) // Filter out empty strings
+    .unique()
+    .map(ByteString::from)
     .collect();
 
-    let (random_samples_correct, random_samples_wrong) =
-        evaluate_test_set(code, &regex_input.regex.complete_regex(), &random_samples)?;
-    Ok((random_samples_correct, random_samples_wrong))
+    let oracle = Oracle::new(&regex_input.regex.complete_regex(), regex_input.match_mode)?;
+    let tests = evaluate_test_set(&oracle, &random_samples);
+    Ok(select_tests_with_simulation(code, tests, sample_number))
 }
 
-fn run_single_standard_test(
+/// Generates mutants of the passing samples (byte flips, insertions, deletions, truncations,
+/// duplicated segments and characters just outside the character classes) and labels them with
+/// the oracle. The mutants are close to the language of the regex, so many of them should fail
+/// and look for inputs that the circuit accepts by mistake.
+fn test_for_mutants(
+    regex_input: &DbEntry,
+    passing_samples: &[ByteString],
+    rng: &mut StdRng,
+) -> anyhow::Result<Vec<NoirTest>> {
+    let complete_regex = regex_input.regex.complete_regex();
+    let mutator = Mutator::new(&complete_regex)?;
+    let mutants: Vec<ByteString> = mutator
+        .mutants(
+            passing_samples,
+            constants::DEFAULT_MUTANT_NUMBER,
+            regex_input.input_size,
+            rng,
+        )
+        .into_iter()
+        .filter(|mutant| !passing_samples.contains(mutant))
+        .unique()
+        .collect();
+
+    let oracle = Oracle::new(&complete_regex, regex_input.match_mode)?;
+    Ok(evaluate_test_set(&oracle, &mutants))
+}
+
+/// Builds inputs aimed at the transitions of the automaton that none of the tests takes, and
+/// labels them with the oracle. Each input follows the shortest path to the transition and then
+/// the shortest path to an accepting state.
+fn test_for_uncovered_transitions(
+    regex_input: &DbEntry,
     code: &Code,
-    standard_test: &String,
-    should_fail: bool, // Whether the Noir test should fail
-) -> Result<bool, anyhow::Error> {
-    run_single_test(code, Some(standard_test), None, should_fail)
+    tests: &[NoirTest],
+) -> anyhow::Result<Vec<NoirTest>> {
+    let dfa = match Dfa::from_noir_code(code.noir_code()) {
+        Ok(dfa) => dfa,
+        Err(err) => {
+            log::info!(
+                "ignoring the inputs for the uncovered transitions due to the following error: {:?}",
+                err
+            );
+            return Ok(Vec::new());
+        }
+    };
+
+    let mut uncovered_transitions = dfa.transitions();
+    for test in tests {
+        for step in dfa.run(test.input()).steps {
+            uncovered_transitions.remove(&step);
+        }
+    }
+    let inputs: Vec<ByteString> = uncovered_transitions
+        .iter()
+        .filter_map(|&transition| dfa.input_through(transition))
+        .filter(|input| !input.is_empty() && input.len() <= regex_input.input_size)
+        .unique()
+        .take(constants::DEFAULT_COVERAGE_SAMPLE_NUMBER)
+        .collect();
+    log::info!(
+        "generated {} inputs for the {} transitions not taken by the samples",
+        inputs.len(),
+        uncovered_transitions.len()
+    );
+
+    let oracle = Oracle::new(&regex_input.regex.complete_regex(), regex_input.match_mode)?;
+    Ok(evaluate_test_set(&oracle, &inputs))
 }
 
-fn run_single_gen_substr_test(
+/// Runs the tests through the automaton of the generated code simulated in Rust. Returns the
+/// tests for which the simulation doesn't give the expected outcome, up to
+/// `DEFAULT_MAX_SIMULATED_DISAGREEMENTS`, and `sample_number` of the tests for which it does.
+fn select_tests_with_simulation(
     code: &Code,
-    gen_substr_test: &InputWithSubstrs,
-) -> Result<bool, anyhow::Error> {
-    // Substr tests should always pass
-    run_single_test(code, None, Some(gen_substr_test), true)
+    tests: Vec<NoirTest>,
+    sample_number: usize,
+) -> Vec<NoirTest> {
+    let dfa = match Dfa::from_noir_code(code.noir_code()) {
+        Ok(dfa) => dfa,
+        Err(err) => {
+            log::info!(
+                "ignoring the simulation of the random samples due to the following error: {:?}",
+                err
+            );
+            return tests.into_iter().take(sample_number).collect();
+        }
+    };
+
+    let total_tests = tests.len();
+    let (agreements, disagreements): (Vec<_>, Vec<_>) =
+        tests.into_iter().partition(|test| match test {
+            NoirTest::Standard { input, should_fail } => dfa.run(input).accepted != *should_fail,
+            NoirTest::Substring(sample) => {
+                let run = dfa.run(&sample.input);
+                run.accepted
+                    && run.substrings_of(&sample.input)
+                        == sample
+                            .expected_substrings
+                            .iter()
+                            .map(ByteString::as_bytes)
+                            .collect::<Vec<_>>()
+            }
+        });
+    log::info!(
+        "the simulated automaton disagrees with the expected outcome for {} out of {} random samples",
+        disagreements.len(),
+        total_tests
+    );
+    if disagreements.len() > constants::DEFAULT_MAX_SIMULATED_DISAGREEMENTS {
+        log::info!(
+            "only the first {} samples on which the simulated automaton disagrees are tested with Noir",
+            constants::DEFAULT_MAX_SIMULATED_DISAGREEMENTS
+        );
+    }
+
+    disagreements
+        .into_iter()
+        .take(constants::DEFAULT_MAX_SIMULATED_DISAGREEMENTS)
+        .chain(agreements.into_iter().take(sample_number))
+        .collect()
 }
 
-/// Write the correct test to the Noir file and run it
-fn run_single_test(
+/// Outcome of a single Noir test function reported by `nargo test`.
+#[derive(Debug, Clone, Default)]
+struct NargoTestOutcome {
+    /// Whether the test had the expected outcome.
+    passed: bool,
+    /// Console output of `nargo test` related to this test.
+    output: String,
+    /// Cause of the failure when it is known without looking at the output of the test.
+    failure_cause: Option<FailureCause>,
+}
+
+/// Writes all the tests into the Noir project and runs them with a single `nargo test`
+/// execution. Returns the outcome of each test in the same order as the tests.
+fn run_test_batch(
     code: &Code,
-    standard_test: Option<&String>,
-    gen_substr_test: Option<&InputWithSubstrs>,
-    should_fail: bool,
-) -> Result<bool, anyhow::Error> {
-    code.write_test_to_path(
-        standard_test,
-        gen_substr_test,
-        should_fail,
-        Path::new(constants::DEFAULT_PROJECT_MAIN_FILE),
-    )?;
-    let test_result = test_noir_code()?;
-    Ok(test_result)
+    tests: &[NoirTest],
+    workspace: &Workspace,
+) -> anyhow::Result<Vec<NargoTestOutcome>> {
+    if tests.is_empty() {
+        return Ok(Vec::new());
+    }
+    code.write_test_to_path(tests, &workspace.project_main_file())?;
+    let (mut test_outcomes, console_output, timed_out) = test_noir_code(workspace)?;
+
+    // A test that does not appear in the output is considered as failed, and the whole output
+    // is attached to it. If no test appears at all, the project didn't compile.
+    let missing_test_cause = if timed_out {
+        FailureCause::Timeout
+    } else if test_outcomes.is_empty() {
+        FailureCause::CompileError
+    } else {
+        FailureCause::Unknown
+    };
+    Ok((0..tests.len())
+        .map(|index| {
+            test_outcomes
+                .remove(&test_fn_name(index))
+                .unwrap_or_else(|| NargoTestOutcome {
+                    passed: false,
+                    output: console_output.clone(),
+                    failure_cause: Some(missing_test_cause),
+                })
+        })
+        .collect())
 }
 
-/// Evaluates a test set of samples and returns a pair of the samples that were successful
-/// and the samples that were not successful.
-fn evaluate_test_set(
-    code: &mut Code,
-    regex: &str,
-    test_set: &Vec<String>,
-) -> anyhow::Result<(Vec<String>, Vec<String>)> {
-    let mut failed_samples = Vec::new();
-    let mut successfull_samples = Vec::new();
+/// Evaluates a test set of samples and returns the Noir tests for them. The oracle decides
+/// whether each sample is expected to pass or fail.
+fn evaluate_test_set(oracle: &Oracle, test_set: &[ByteString]) -> Vec<NoirTest> {
+    let mut tests = Vec::new();
     for string in test_set {
-        let ground_truth_checker = regex::Regex::new(regex)
-            .context("error parsing the regex in the ground truth checker")?;
-        // Check with a Rust regex lib whether this input should pass
-        let ground_truth_result = ground_truth_checker.captures(string).is_some();
+        // Check with the oracle whether this input should pass
+        let ground_truth_result = oracle.accepts(string);
 
         // Use the Rust regex result to decide whether this test should pass of fail
-        let correct_result = run_single_standard_test(code, string, !ground_truth_result)?;
-
-        if !correct_result {
-            failed_samples.push(string.clone());
-        } else {
-            successfull_samples.push(string.clone());
-        }
+        tests.push(NoirTest::Standard {
+            input: string.clone(),
+            should_fail: !ground_truth_result,
+        });
     }
-    
-    Ok((successfull_samples, failed_samples))
+
+    tests
 }
 
 /// Executes the `nargo test` command on the Noir project to test the result of the regex
-/// from the Noir perspective.
-fn test_noir_code() -> anyhow::Result<bool> {
-    let output = Command::new("nargo")
+/// from the Noir perspective. Returns the outcome of each test function, indexed by the name
+/// of the function, the whole console output and whether the command was stopped because it
+/// exceeded the time limit.
+fn test_noir_code(
+    workspace: &Workspace,
+) -> anyhow::Result<(HashMap<String, NargoTestOutcome>, String, bool)> {
+    // Both streams are written to the same pipe so that the errors printed by a test stay
+    // next to its `Testing <test_name>...` line.
+    let (output_pipe, output_writer) =
+        io::pipe().context("error creating the pipe for the output of the test command")?;
+    let mut child = Command::new("nargo")
         .arg("test")
-        .current_dir(constants::DEFAULT_PROJECT_PATH)
-        .output()
+        .current_dir(workspace.project_path())
+        .stdout(
+            output_writer
+                .try_clone()
+                .context("error creating the pipe for the output of the test command")?,
+        )
+        .stderr(output_writer)
+        .spawn()
         .context("the test command was not executed successfully")?;
-    Ok(output.status.success())
+
+    // The output is read in a separate thread so that the command doesn't block when the
+    // pipe is full while we wait for it.
+    let output_reader = read_in_background(Some(output_pipe));
+
+    let timeout = Duration::from_secs(constants::DEFAULT_NARGO_TEST_TIMEOUT_SECS);
+    let start = Instant::now();
+    let mut timed_out = false;
+    while child
+        .try_wait()
+        .context("error waiting for the test command")?
+        .is_none()
+    {
+        if start.elapsed() > timeout {
+            log::warn!("nargo test exceeded the time limit of {:?}", timeout);
+            child.kill().context("error stopping the test command")?;
+            child.wait().context("error waiting for the test command")?;
+            timed_out = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let console_output = output_reader.join().unwrap_or_default();
+    Ok((
+        parse_nargo_test_output(&console_output),
+        console_output,
+        timed_out,
+    ))
+}
+
+/// Reads the whole pipe in a separate thread.
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).into_owned()
+    })
+}
+
+/// Parses the lines `[<project>] Testing <test_name>... ok|FAIL` reported by `nargo test`.
+/// The lines printed after a test and before the next one (e.g. the failed assertion) are
+/// kept as the output of that test.
+fn parse_nargo_test_output(console_output: &str) -> HashMap<String, NargoTestOutcome> {
+    let mut test_outcomes = HashMap::new();
+    let mut current_test: Option<(String, NargoTestOutcome)> = None;
+    for line in console_output.lines() {
+        let test_report = line
+            .split_once("Testing ")
+            .and_then(|(_, test_report)| test_report.split_once("..."));
+        if let Some((test_name, outcome)) = test_report {
+            test_outcomes.extend(current_test.take());
+            current_test = Some((
+                test_name.trim().to_string(),
+                NargoTestOutcome {
+                    passed: outcome.trim_start().starts_with("ok"),
+                    output: format!("{}\n", line),
+                    failure_cause: None,
+                },
+            ));
+        } else if [
+            " test passed",
+            " tests passed",
+            " test failed",
+            " tests failed",
+        ]
+        .iter()
+        .any(|summary| line.contains(summary))
+        {
+            // Final line with the number of passed and failed tests
+            test_outcomes.extend(current_test.take());
+        } else if let Some((_, outcome)) = current_test.as_mut() {
+            outcome.output.push_str(line);
+            outcome.output.push('\n');
+        }
+    }
+    test_outcomes.extend(current_test);
+    test_outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_the_errors_to_the_failed_test() {
+        let console_output = "\
+[execution_project] Running 3 test functions
+[execution_project] Testing test_0... ok
+[execution_project] Testing test_1... FAIL
+error: Failed constraint: 'Match not found'
+[execution_project] Testing test_2... ok
+[execution_project] 2 tests passed, 1 test failed
+";
+        let outcomes = parse_nargo_test_output(console_output);
+        assert_eq!(outcomes.len(), 3);
+        assert!(outcomes["test_0"].passed);
+        assert!(!outcomes["test_1"].passed);
+        assert!(outcomes["test_1"].output.contains("Match not found"));
+        assert!(!outcomes["test_2"].output.contains("Match not found"));
+    }
+
+    #[test]
+    fn skips_the_random_samples_of_a_regex_without_capture_groups() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (tests, skipped_tests) =
+            test_random_samples_capture_groups("ab+c", 64, MatchMode::Search, 10, &mut rng)
+                .unwrap();
+        assert!(tests.is_empty());
+        assert!(skipped_tests.is_empty());
+    }
+
+    #[test]
+    fn generates_the_same_samples_and_mutants_with_the_same_seed() {
+        let regex = "subject:[^\r\n]+\r\n";
+        let generate = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let samples: Vec<ByteString> = sample_generator(regex, 8)
+                .unwrap()
+                .sample_iter(&mut rng)
+                .take(20)
+                .map(|sample: Vec<u8>| sample.into())
+                .collect();
+            let mutants = Mutator::new(regex)
+                .unwrap()
+                .mutants(&samples, 20, 64, &mut rng);
+            (samples, mutants)
+        };
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7), generate(8));
+    }
 }
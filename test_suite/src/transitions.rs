@@ -0,0 +1,305 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+use anyhow::Context;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+use crate::{
+    code, constants,
+    db::{RawRegex, RegexInput, Transitions},
+    dfa::Dfa,
+    workspace::Workspace,
+};
+
+/// Errors that can arise when deriving the substring transitions from the capture groups.
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// The regex has no capture group, so there is no substring to extract.
+    #[error("the regex has no capture groups to mark the substrings")]
+    NoCaptureGroups,
+    /// A capture group is inside another one.
+    #[error("nested capture groups are not supported")]
+    NestedCaptureGroups,
+    /// The random samples never went through a capture group.
+    #[error("no sample matched the capture group {0}")]
+    UnmatchedCaptureGroup(usize),
+    /// The same transitions of the automaton are taken inside and outside a capture group,
+    /// or inside different capture groups, so the substrings can't be extracted by zk-regex.
+    #[error("the capture groups don't match the states of the automaton, the transitions {0:?} are shared")]
+    SharedTransitions(Vec<(u32, u32)>),
+}
+
+/// Returns the regex that is passed to zk-regex for a raw regex where the capture groups mark
+/// the substrings: the non-capturing groups `(?:...)` and the named groups `(?P<name>...)` are
+/// written as plain groups.
+pub fn plain_regex(regex: &str) -> String {
+    let mut plain = String::with_capacity(regex.len());
+    let mut chars = regex.chars().peekable();
+    let mut in_class = false;
+    while let Some(c) = chars.next() {
+        plain.push(c);
+        match c {
+            '\\' => plain.extend(chars.next()),
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class && chars.peek() == Some(&'?') => {
+                let group_prefix: String = chars.clone().take_while(|&c| c != ')').collect();
+                if group_prefix.starts_with("?:") {
+                    chars.nth(1);
+                } else if let Some(name_end) = (group_prefix.starts_with("?P<")
+                    || group_prefix.starts_with("?<"))
+                .then(|| group_prefix.find('>'))
+                .flatten()
+                {
+                    chars.nth(name_end);
+                }
+            }
+            _ => {}
+        }
+    }
+    plain
+}
+
+/// Derives the substring transitions of a raw regex in which every capture group marks a
+/// substring. The regex is compiled with zk-regex so the states are numbered as in the
+/// generated code, and random samples of the regex are run through the automaton: the
+/// transitions taken while reading the bytes of the i-th capture group are the transitions of
+/// the i-th substring.
+///
+/// Since the transitions are found by sampling, a transition that none of the
+/// [`DEFAULT_DERIVATION_SAMPLE_NUMBER`](constants::DEFAULT_DERIVATION_SAMPLE_NUMBER) samples
+/// goes through is missing from the result.
+pub fn derive_transitions(regex: &str, workspace: &Workspace) -> anyhow::Result<Transitions> {
+    let sampler = CaptureSampler::new(regex, constants::DEFAULT_DERIVATION_MAX_REPEAT)?;
+    let noir_code = code::generate_noir_code(
+        &RegexInput::Raw(RawRegex::Simple(regex.to_string())),
+        false,
+        workspace,
+    )
+    .context("error generating the automaton of the regex")?;
+    let dfa = Dfa::from_noir_code(&noir_code)?;
+    let full_match = regex::bytes::Regex::new(&format!("^(?:{})$", regex))
+        .context("error parsing the regex in the ground truth checker")?;
+
+    // Capture groups (`None` for the bytes outside of the groups) in which each transition is taken
+    let mut transition_groups: BTreeMap<(u32, u32), BTreeSet<Option<usize>>> = BTreeMap::new();
+    // A fixed seed, so the same regex always gets the same transitions
+    let mut rng = StdRng::seed_from_u64(constants::DERIVATION_SEED);
+    for _ in 0..constants::DEFAULT_DERIVATION_SAMPLE_NUMBER {
+        let sample = sampler.sample(&mut rng, usize::MAX);
+        // Samples that rely on the reset of the automaton don't tell which path the
+        // capture groups take
+        if !full_match.is_match(&sample.input) {
+            continue;
+        }
+        let Some(steps) = dfa.trace(&sample.input) else {
+            continue;
+        };
+        for (position, step) in steps.into_iter().enumerate() {
+            let group = sample
+                .captures
+                .iter()
+                .find(|(_, span)| span.contains(&position))
+                .map(|(group, _)| *group);
+            transition_groups.entry(step).or_default().insert(group);
+        }
+    }
+
+    let shared_transitions: Vec<(u32, u32)> = transition_groups
+        .iter()
+        .filter(|(_, groups)| groups.len() > 1)
+        .map(|(transition, _)| *transition)
+        .collect();
+    if !shared_transitions.is_empty() {
+        anyhow::bail!(Error::SharedTransitions(shared_transitions));
+    }
+
+    let transitions = (1..=sampler.capture_count())
+        .map(|group| {
+            let group_transitions: Vec<Vec<u32>> = transition_groups
+                .iter()
+                .filter(|(_, groups)| groups.contains(&Some(group)))
+                .map(|((s, s_next), _)| vec![*s, *s_next])
+                .collect();
+            if group_transitions.is_empty() {
+                anyhow::bail!(Error::UnmatchedCaptureGroup(group));
+            }
+            Ok(group_transitions)
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Transitions { transitions })
+}
+
+/// A random string of a regex together with the position of each capture group in it.
+pub struct CaptureSample {
+    /// The bytes of the generated string, which are not valid UTF-8 if the regex matches
+    /// arbitrary bytes, as in `(?-u:\xff)`.
+    pub input: Vec<u8>,
+    /// Index and byte range of every match of a capture group. A group inside a repetition
+    /// has one range per iteration.
+    pub captures: Vec<(usize, Range<usize>)>,
+}
+
+impl CaptureSample {
+    /// Returns the substrings that zk-regex extracts from the sample: the bytes of the capture
+    /// groups, where consecutive bytes form a single substring.
+    pub fn expected_substrings(&self) -> Vec<Vec<u8>> {
+        let mut spans: Vec<Range<usize>> = self
+            .captures
+            .iter()
+            .map(|(_, span)| span.clone())
+            .filter(|span| !span.is_empty())
+            .collect();
+        spans.sort_by_key(|span| span.start);
+
+        let mut substrings: Vec<Range<usize>> = Vec::new();
+        for span in spans {
+            match substrings.last_mut() {
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => substrings.push(span),
+            }
+        }
+        substrings
+            .into_iter()
+            .map(|span| self.input[span].to_vec())
+            .collect()
+    }
+}
+
+/// Generator of random strings of a regex that keeps track of the capture groups. The
+/// `rand_regex` library only returns the strings, so the regex is sampled from its syntax tree.
+pub struct CaptureSampler {
+    /// Syntax tree of the regex.
+    hir: Hir,
+    /// Maximum number of repetitions above the minimum of the repetition operators.
+    max_repeat: u32,
+}
+
+impl CaptureSampler {
+    /// Creates the generator for a regex with at least one capture group, none of them nested.
+    pub fn new(regex: &str, max_repeat: u32) -> anyhow::Result<Self> {
+        let hir = regex_syntax::ParserBuilder::new()
+            .utf8(false)
+            .build()
+            .parse(regex)
+            .context("error parsing the regex")?;
+        if hir.properties().explicit_captures_len() == 0 {
+            anyhow::bail!(Error::NoCaptureGroups);
+        }
+        if has_nested_captures(&hir, false) {
+            anyhow::bail!(Error::NestedCaptureGroups);
+        }
+        Ok(Self { hir, max_repeat })
+    }
+
+    /// Number of capture groups in the regex.
+    pub fn capture_count(&self) -> usize {
+        self.hir.properties().explicit_captures_len()
+    }
+
+    /// Generates a random string of the regex. Once the string reaches `max_len` bytes, the
+    /// repetition operators only repeat their minimum number of times, so the result is
+    /// usually not much longer than `max_len`.
+    pub fn sample<R: Rng>(&self, rng: &mut R, max_len: usize) -> CaptureSample {
+        let mut sample = CaptureSample {
+            input: Vec::new(),
+            captures: Vec::new(),
+        };
+        self.sample_hir(&self.hir, rng, max_len, &mut sample);
+        sample
+    }
+
+    fn sample_hir<R: Rng>(
+        &self,
+        hir: &Hir,
+        rng: &mut R,
+        max_len: usize,
+        sample: &mut CaptureSample,
+    ) {
+        match hir.kind() {
+            // The anchors and boundaries don't produce any character
+            HirKind::Empty | HirKind::Look(_) => {}
+            HirKind::Literal(literal) => sample.input.extend_from_slice(&literal.0),
+            HirKind::Class(Class::Unicode(class)) => {
+                // Most of the regexes work on ASCII text, so the ASCII characters of the class
+                // are preferred
+                let ascii_ranges: Vec<(u32, u32)> = class
+                    .ranges()
+                    .iter()
+                    .filter(|range| range.start().is_ascii())
+                    .map(|range| (range.start() as u32, (range.end() as u32).min(0x7f)))
+                    .collect();
+                let ranges: Vec<(u32, u32)> = if !ascii_ranges.is_empty() && rng.gen_ratio(3, 4) {
+                    ascii_ranges
+                } else {
+                    class
+                        .ranges()
+                        .iter()
+                        .map(|range| (range.start() as u32, range.end() as u32))
+                        .collect()
+                };
+                if ranges.is_empty() {
+                    return;
+                }
+                let (start, end) = ranges[rng.gen_range(0..ranges.len())];
+                // The fallback is only used when a surrogate is picked in a range that spans
+                // over them
+                let c = char::from_u32(rng.gen_range(start..=end))
+                    .unwrap_or(char::from_u32(start).unwrap_or_default());
+                sample
+                    .input
+                    .extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                if class.ranges().is_empty() {
+                    return;
+                }
+                let range = class.ranges()[rng.gen_range(0..class.ranges().len())];
+                sample
+                    .input
+                    .push(rng.gen_range(range.start()..=range.end()));
+            }
+            HirKind::Repetition(repetition) => {
+                let max = repetition
+                    .max
+                    .unwrap_or(u32::MAX)
+                    .min(repetition.min.saturating_add(self.max_repeat));
+                for iteration in 0..rng.gen_range(repetition.min..=max) {
+                    if iteration >= repetition.min && sample.input.len() >= max_len {
+                        break;
+                    }
+                    self.sample_hir(&repetition.sub, rng, max_len, sample);
+                }
+            }
+            HirKind::Capture(capture) => {
+                let start = sample.input.len();
+                self.sample_hir(&capture.sub, rng, max_len, sample);
+                sample
+                    .captures
+                    .push((capture.index as usize, start..sample.input.len()));
+            }
+            HirKind::Concat(subs) => {
+                for sub in subs {
+                    self.sample_hir(sub, rng, max_len, sample);
+                }
+            }
+            HirKind::Alternation(subs) => {
+                self.sample_hir(&subs[rng.gen_range(0..subs.len())], rng, max_len, sample)
+            }
+        }
+    }
+}
+
+/// Returns whether there is a capture group inside another capture group.
+fn has_nested_captures(hir: &Hir, inside_capture: bool) -> bool {
+    match hir.kind() {
+        HirKind::Capture(capture) => inside_capture || has_nested_captures(&capture.sub, true),
+        kind => kind
+            .subs()
+            .iter()
+            .any(|sub| has_nested_captures(sub, inside_capture)),
+    }
+}
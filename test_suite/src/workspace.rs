@@ -0,0 +1,123 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use anyhow::Context;
+
+use crate::constants;
+
+/// Set of files and folders used to generate, test and benchmark the Noir code for a
+/// database entry. All the paths are relative to the root of the workspace.
+///
+/// The default workspace is the current directory. Isolated workspaces are temporary
+/// copies of the execution project so that several entries can be processed at the same
+/// time without overwriting each other's files.
+pub struct Workspace {
+    /// Folder that contains the Noir project and the intermediate files.
+    root: PathBuf,
+    /// Whether the root folder is a temporary copy that must be removed afterwards.
+    temporary: bool,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("."),
+            temporary: false,
+        }
+    }
+}
+
+impl Workspace {
+    /// Creates a temporary workspace for the given worker with its own copy of the
+    /// execution project.
+    pub fn isolated(worker_id: usize) -> anyhow::Result<Self> {
+        let root = std::env::temp_dir().join(format!(
+            "zk-regex-test-suite-{}-worker-{}",
+            process::id(),
+            worker_id
+        ));
+        if root.exists() {
+            fs::remove_dir_all(&root)
+                .context(format!("error cleaning the workspace {:?}", root))?;
+        }
+        let workspace = Self {
+            root,
+            temporary: true,
+        };
+        copy_project(
+            Path::new(constants::DEFAULT_PROJECT_PATH),
+            &workspace.project_path(),
+        )
+        .context(format!(
+            "error copying the execution project into the workspace {:?}",
+            workspace.root
+        ))?;
+        Ok(workspace)
+    }
+
+    /// Path of the Noir project.
+    pub fn project_path(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_PROJECT_PATH)
+    }
+
+    /// Path of the main file in the Noir project.
+    pub fn project_main_file(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_PROJECT_MAIN_FILE)
+    }
+
+    /// Path of the Prover.toml file in the Noir project.
+    pub fn prover_toml_path(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_PROVER_TOML_PATH)
+    }
+
+    /// Path for the Noir file that will contain the generated code using the zk-regex tool.
+    pub fn generation_path(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_GENERATION_PATH)
+    }
+
+    /// Path of the JSON file that stores the decomposed version of a regex.
+    pub fn decomposed_json_file(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_DECOMPOSED_JSON_FILE)
+    }
+
+    /// Path of the JSON file that stores the transitions of where substrings should be extracted.
+    pub fn substrs_json_path(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_SUBSTRS_JSON_PATH)
+    }
+
+    /// Path of the timing report.
+    pub fn proving_time_result_file(&self) -> PathBuf {
+        self.root.join(constants::DEFAULT_PROVING_TIME_RESULT_FILE)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if self.temporary {
+            if let Err(err) = fs::remove_dir_all(&self.root) {
+                log::warn!("error removing the workspace {:?}: {}", self.root, err);
+            }
+        }
+    }
+}
+
+/// Copies the Noir project recursively, skipping the compilation artifacts.
+fn copy_project(source: &Path, destination: &Path) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        if entry.file_name() == "target" {
+            continue;
+        }
+        let destination_path = destination.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_project(&entry.path(), &destination_path)?;
+        } else {
+            fs::copy(entry.path(), destination_path)?;
+        }
+    }
+    Ok(())
+}